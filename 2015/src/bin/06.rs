@@ -1,3 +1,4 @@
+use adv_code_2015::rect_grid::{RectGrid, Rectangle};
 use adv_code_2015::start_day;
 use anyhow::{Result, anyhow};
 use const_format::concatcp;
@@ -20,6 +21,12 @@ struct Instruction {
     c2: Coordinate,
 }
 
+impl Instruction {
+    fn rectangle(&self) -> Rectangle {
+        Rectangle::new((self.c1.x, self.c1.y), (self.c2.x, self.c2.y))
+    }
+}
+
 #[derive(Debug, PartialEq, Eq)]
 enum Kind {
     TurnOn,
@@ -69,80 +76,30 @@ fn parse<R: BufRead>(reader: R) -> Result<Vec<Instruction>> {
     reader.lines().map(|l| parse_instruction(&l?)).collect()
 }
 
-fn part1(instructions: &[Instruction]) -> usize {
-    let mut lights = vec![vec![false; 1000]; 1000];
-    for i in instructions {
-        // Matching outside the loop lets us get ~30x better perf than matching inside
+fn part1(instructions: &[Instruction]) -> Result<u64> {
+    let rects: Vec<_> = instructions.iter().map(Instruction::rectangle).collect();
+    let mut lights = RectGrid::new(1000, 1000, &rects, false)?;
+    for (i, rect) in instructions.iter().zip(&rects) {
         match i.kind {
-            Kind::TurnOn => {
-                for row in lights.get_mut(i.c1.x..=i.c2.x).into_iter().flatten() {
-                    if let Some(slice) = row.get_mut(i.c1.y..=i.c2.y) {
-                        slice.fill(true);
-                    }
-                }
-            }
-            Kind::TurnOff => {
-                for row in lights.get_mut(i.c1.x..=i.c2.x).into_iter().flatten() {
-                    if let Some(slice) = row.get_mut(i.c1.y..=i.c2.y) {
-                        slice.fill(false);
-                    }
-                }
-            }
-            Kind::Toggle => {
-                for row in lights.get_mut(i.c1.x..=i.c2.x).into_iter().flatten() {
-                    if let Some(slice) = row.get_mut(i.c1.y..=i.c2.y) {
-                        for light in slice {
-                            *light = !*light;
-                        }
-                    }
-                }
-            }
-        }
+            Kind::TurnOn => lights.fill(rect, &true),
+            Kind::TurnOff => lights.fill(rect, &false),
+            Kind::Toggle => lights.update(rect, |light| *light = !*light),
+        }?;
     }
-    lights
-        .iter()
-        .map(|r| r.iter().filter(|&&b| b).count())
-        .sum()
+    lights.count_where(|&light| light)
 }
 
-fn part2(instructions: &[Instruction]) -> u64 {
-    let mut lights = vec![vec![0u16; 1000]; 1000];
-    for i in instructions {
-        // Like above, matching outside the loop lets us get ~30x better perf
+fn part2(instructions: &[Instruction]) -> Result<u64> {
+    let rects: Vec<_> = instructions.iter().map(Instruction::rectangle).collect();
+    let mut lights = RectGrid::new(1000, 1000, &rects, 0u64)?;
+    for (i, rect) in instructions.iter().zip(&rects) {
         match i.kind {
-            Kind::TurnOn => {
-                for row in lights.get_mut(i.c1.x..=i.c2.x).into_iter().flatten() {
-                    if let Some(slice) = row.get_mut(i.c1.y..=i.c2.y) {
-                        for light in slice {
-                            *light += 1;
-                        }
-                    }
-                }
-            }
-            Kind::TurnOff => {
-                for row in lights.get_mut(i.c1.x..=i.c2.x).into_iter().flatten() {
-                    if let Some(slice) = row.get_mut(i.c1.y..=i.c2.y) {
-                        for light in slice {
-                            *light = light.saturating_sub(1);
-                        }
-                    }
-                }
-            }
-            Kind::Toggle => {
-                for row in lights.get_mut(i.c1.x..=i.c2.x).into_iter().flatten() {
-                    if let Some(slice) = row.get_mut(i.c1.y..=i.c2.y) {
-                        for light in slice {
-                            *light += 2;
-                        }
-                    }
-                }
-            }
-        }
+            Kind::TurnOn => lights.update(rect, |light| *light += 1),
+            Kind::TurnOff => lights.update(rect, |light| *light = light.saturating_sub(1)),
+            Kind::Toggle => lights.update(rect, |light| *light += 2),
+        }?;
     }
-    lights
-        .iter()
-        .map(|r| r.iter().map(|&v| u64::from(v)).sum::<u64>())
-        .sum()
+    lights.sum_by(|&light| light)
 }
 
 fn main() -> Result<()> {
@@ -156,13 +113,13 @@ fn main() -> Result<()> {
 
     println!("=== Part 1 ===");
     let p1_time = Instant::now();
-    let result = part1(&input);
+    let result = part1(&input)?;
     println!("Result = {result}");
     println!("Elapsed = {:.2?}", p1_time.elapsed());
 
     println!("\n=== Part 2 ===");
     let p2_time = Instant::now();
-    let result = part2(&input);
+    let result = part2(&input)?;
     println!("Result = {result}");
     println!("Elapsed = {:.2?}", p2_time.elapsed());
 
@@ -208,7 +165,7 @@ turn off 499,499 through 500,500
     fn part_1() {
         let expected = 998_996;
         let input = super::parse(BufReader::new(TEST.as_bytes())).expect("parse succeeds");
-        let result = part1(&input);
+        let result = part1(&input).expect("succeeds");
         assert_eq!(result, expected)
     }
 
@@ -216,7 +173,19 @@ turn off 499,499 through 500,500
     fn part_2() {
         let expected = 1_001_996;
         let input = super::parse(BufReader::new(TEST.as_bytes())).expect("parse succeeds");
-        let result = part2(&input);
+        let result = part2(&input).expect("succeeds");
         assert_eq!(result, expected)
     }
+
+    #[test]
+    fn out_of_bounds() {
+        let input = [Instruction {
+            kind: Kind::TurnOn,
+            c1: Coordinate { x: 0, y: 0 },
+            c2: Coordinate { x: 1000, y: 5 },
+        }];
+        assert!(part1(&input).is_err());
+        assert!(part2(&input).is_err());
+        assert!(super::parse(BufReader::new(&b"toggle 0,0 through 1000,5"[..])).is_err());
+    }
 }
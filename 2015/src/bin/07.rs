@@ -1,11 +1,17 @@
+use adv_code_2015::graph::dot::Dot;
+use adv_code_2015::graph::scc::ensure_acyclic;
+use adv_code_2015::graph::{Direction, Graph, Storage};
 use adv_code_2015::start_day;
 use anyhow::{Result, anyhow};
 use const_format::concatcp;
-use std::collections::HashMap;
+use itertools::Itertools;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
 use std::time::Instant;
 
 const DAY: &str = "07";
 const INPUT_FILE: &str = concatcp!("input/", DAY, ".txt");
+const DOT_FILE: &str = concatcp!("output/", DAY, ".dot");
 
 enum GateRef {
     Literal(u16),
@@ -77,11 +83,78 @@ impl Circuit {
     fn update(&mut self, id: &str, g: LogicGate) -> Option<LogicGate> {
         self.parts.insert(id.to_string(), g)
     }
+
+    /// Wires as nodes, with an edge from each wire into the gates it feeds
+    fn wiring(&self) -> Graph<()> {
+        let mut g = Graph::new(Direction::Directed, Storage::AdjacencyList);
+        for (name, gate) in &self.parts {
+            g.add_node(name);
+            for input in gate.inputs() {
+                g.add_edge(input, name, ());
+            }
+        }
+        g
+    }
+
+    /// The circuit in DOT format, with an edge from each wire into the gates it feeds. Nodes show
+    /// the gate driving each wire and its signal, if that can be computed.
+    fn to_dot(&self, highlight: &HashSet<&str>) -> String {
+        let mut cache = HashMap::new();
+        let mut dot = Dot::new(Direction::Directed);
+        for (name, gate) in self.parts.iter().sorted_by_key(|(name, _)| name.as_str()) {
+            let signal = self
+                .value_inner(&GateRef::Reference(name.clone()), &mut cache)
+                .map_or_else(|_| "?".to_string(), |v| v.to_string());
+            let label = format!("{name} = {gate}\n{signal}");
+            dot.node(name, Some(&label), highlight.contains(name.as_str()));
+            for input in gate.inputs() {
+                dot.edge(input, name, None, false);
+            }
+        }
+        dot.render()
+    }
+}
+
+impl fmt::Display for GateRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Literal(v) => write!(f, "{v}"),
+            Self::Reference(g) => write!(f, "{g}"),
+        }
+    }
+}
+
+impl LogicGate {
+    /// Names of the wires this gate reads from
+    fn inputs(&self) -> impl Iterator<Item = &str> {
+        let (a, b) = match self {
+            Self::And(l, r) | Self::Or(l, r) => (l, Some(r)),
+            Self::Wire(g) | Self::LShift(g, _) | Self::Not(g) | Self::RShift(g, _) => (g, None),
+        };
+        std::iter::once(a).chain(b).filter_map(|g| match g {
+            GateRef::Reference(name) => Some(name.as_str()),
+            GateRef::Literal(_) => None,
+        })
+    }
+}
+
+impl fmt::Display for LogicGate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Wire(g) => write!(f, "{g}"),
+            Self::And(l, r) => write!(f, "{l} AND {r}"),
+            Self::LShift(g, amount) => write!(f, "{g} LSHIFT {amount}"),
+            Self::Not(g) => write!(f, "NOT {g}"),
+            Self::Or(l, r) => write!(f, "{l} OR {r}"),
+            Self::RShift(g, amount) => write!(f, "{g} RSHIFT {amount}"),
+        }
+    }
 }
 
 mod parse {
     use super::GateRef;
     use super::LogicGate;
+    use super::ensure_acyclic;
     use anyhow::{Result, anyhow};
     use nom::branch::alt;
     use nom::bytes::complete::tag;
@@ -137,9 +210,12 @@ mod parse {
         .parse(input)
         .finish()
         .map_err(|e| anyhow!("parsing error: {e:?}"))?;
-        Ok(super::Circuit {
+        let circuit = super::Circuit {
             parts: parts.into_iter().collect(),
-        })
+        };
+        // A feedback loop would send evaluation round in circles
+        ensure_acyclic(&circuit.wiring())?;
+        Ok(circuit)
     }
 }
 
@@ -153,6 +229,18 @@ fn part2(input: &mut Circuit) -> Result<u16> {
     input.value("a")
 }
 
+/// Wires to highlight if `--dot` was given, taken from a comma-separated list after it, as in
+/// `--dot a,b`
+fn dot_highlight(args: &[String]) -> Option<HashSet<&str>> {
+    let at = args.iter().position(|arg| arg == "--dot")?;
+    let wires = args
+        .get(at + 1)
+        .filter(|list| !list.starts_with("--"))
+        .map(|list| list.split(',').filter(|w| !w.is_empty()).collect())
+        .unwrap_or_default();
+    Some(wires)
+}
+
 fn main() -> Result<()> {
     start_day(DAY);
 
@@ -162,6 +250,16 @@ fn main() -> Result<()> {
     let mut input = parse::parse(&input_data)?;
     println!("Parsing time = {:.2?}\n", parse_time.elapsed());
 
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(wires) = dot_highlight(&args) {
+        if let Some(unknown) = wires.iter().find(|&&w| !input.parts.contains_key(w)) {
+            return Err(anyhow!("no wire named {unknown} to highlight"));
+        }
+        std::fs::create_dir_all("output")?;
+        std::fs::write(DOT_FILE, input.to_dot(&wires))?;
+        println!("Wrote {DOT_FILE}\n");
+    }
+
     println!("=== Part 1 ===");
     let p1_time = Instant::now();
     let result = part1(&input)?;
@@ -179,7 +277,9 @@ fn main() -> Result<()> {
 
 #[cfg(test)]
 mod tests {
+    use super::dot_highlight;
     use super::parse::parse;
+    use std::collections::HashSet;
 
     const TEST: &str = "\
 123 -> x
@@ -217,4 +317,41 @@ y -> z
 
         assert!(circuit.value("foo").is_err());
     }
+
+    #[test]
+    fn test_feedback_loop() {
+        let err = parse("a AND b -> c\nc -> d\nNOT d -> b\n1 -> a\n")
+            .err()
+            .expect("cyclic circuit is rejected");
+        assert_eq!("graph has cycles through [b, c, d]", err.to_string());
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let circuit = parse(TEST).expect("parse succeeds");
+        let dot = circuit.to_dot(&HashSet::from(["d"]));
+        assert!(dot.starts_with("digraph {\n"));
+        assert!(dot.contains("\"d\" [label=\"d = x AND y\\n72\", style=filled, fillcolor=gold];"));
+        assert!(dot.contains("\"f\" [label=\"f = x LSHIFT 2\\n492\"];"));
+        assert!(dot.contains("\"q\" [label=\"q = 1 AND 0\\n0\"];"));
+        assert!(dot.contains("\"x\" -> \"d\";"));
+        assert!(dot.contains("\"y\" -> \"d\";"));
+        assert_eq!(9, dot.matches(" -> ").count());
+    }
+
+    #[test]
+    fn test_dot_highlight() {
+        let args =
+            |list: &[&str]| -> Vec<String> { list.iter().map(ToString::to_string).collect() };
+        assert_eq!(None, dot_highlight(&args(&["07"])));
+        assert_eq!(Some(HashSet::new()), dot_highlight(&args(&["07", "--dot"])));
+        assert_eq!(
+            Some(HashSet::from(["a", "lx"])),
+            dot_highlight(&args(&["07", "--dot", "a,lx"]))
+        );
+        assert_eq!(
+            Some(HashSet::new()),
+            dot_highlight(&args(&["07", "--dot", "--other"]))
+        );
+    }
 }
@@ -1,71 +1,26 @@
+use adv_code_2015::graph::hamiltonian::{Objective, Route, held_karp};
+use adv_code_2015::graph::{Direction, Graph, Storage};
 use adv_code_2015::start_day;
 use anyhow::{Result, anyhow};
 use const_format::concatcp;
-use itertools::Itertools;
 use nom::bytes::complete::tag;
 use nom::character::complete::{alpha1, usize};
 use nom::combinator::all_consuming;
 use nom::{Finish, IResult, Parser};
-use std::collections::HashMap;
 use std::io::{BufRead, BufReader};
 use std::time::Instant;
 
 const DAY: &str = "09";
 const INPUT_FILE: &str = concatcp!("input/", DAY, ".txt");
 
-#[derive(Default)]
-struct WeightedGraph {
-    ids: HashMap<String, usize>,
-    weights: Vec<Vec<usize>>,
-}
-
-impl WeightedGraph {
-    fn ensure_id(&mut self, n: &str) -> usize {
-        if let Some(&id) = self.ids.get(n) {
-            id
-        } else {
-            let next_id = self.ids.len();
-            self.ids.insert(n.to_string(), next_id);
-            for w in &mut self.weights {
-                w.push(0);
-            }
-            self.weights.push(vec![0; next_id + 1]);
-            next_id
-        }
-    }
-
-    fn update_weight(&mut self, n: usize, m: usize, weight: usize) {
-        if let Some(cell) = self.weights.get_mut(n).and_then(|ws| ws.get_mut(m)) {
-            *cell = weight;
-        } else {
-            unreachable!("for valid ids n, m, should be unreachable");
-        }
-
-        if let Some(cell) = self.weights.get_mut(m).and_then(|ws| ws.get_mut(n)) {
-            *cell = weight;
-        } else {
-            unreachable!("for valid ids n, m, should be unreachable");
-        }
-    }
-
-    fn add_edge(&mut self, n: &str, m: &str, weight: usize) {
-        let (n_id, m_id) = (self.ensure_id(n), self.ensure_id(m));
-        self.update_weight(n_id, m_id, weight);
-    }
-
-    fn num_nodes(&self) -> usize {
-        self.ids.len()
-    }
-}
-
 fn parse_line(s: &str) -> IResult<&str, (&str, &str, usize)> {
     all_consuming((alpha1, tag(" to "), alpha1, tag(" = "), usize))
         .parse(s)
         .map(|(res, (n, _, m, _, w))| (res, (n, m, w)))
 }
 
-fn parse<R: BufRead>(reader: R) -> Result<WeightedGraph> {
-    let mut graph = WeightedGraph::default();
+fn parse<R: BufRead>(reader: R) -> Result<Graph<usize>> {
+    let mut graph = Graph::new(Direction::Undirected, Storage::Dense);
     for res_l in reader.lines() {
         let line = res_l.map_err(|e| anyhow!("error reading line {e}"))?;
         let (_, (n, m, w)) = parse_line(line.as_str())
@@ -76,42 +31,16 @@ fn parse<R: BufRead>(reader: R) -> Result<WeightedGraph> {
     Ok(graph)
 }
 
-fn route_weights_iter(input: &WeightedGraph) -> impl Iterator<Item = usize> {
-    let n = input.num_nodes();
-    (0..n).permutations(n).map(|order| {
-        order
-            .iter()
-            .tuple_windows()
-            .map(|(&n, &m)| {
-                input
-                    .weights
-                    .get(n)
-                    .expect("exists by construction")
-                    .get(m)
-                    .expect("exists by construction")
-            })
-            .sum()
-    })
+fn shortest_or_longest(input: &Graph<usize>, objective: Objective) -> usize {
+    held_karp(input, objective, Route::Path, None).map_or(0, |tour| tour.score())
 }
 
-fn part1(input: &WeightedGraph) -> usize {
-    if input.num_nodes() == 0 {
-        return 0;
-    }
-
-    route_weights_iter(input)
-        .min()
-        .expect("at least one permutation, for non-empty graph")
+fn part1(input: &Graph<usize>) -> usize {
+    shortest_or_longest(input, Objective::Minimize)
 }
 
-fn part2(input: &WeightedGraph) -> usize {
-    if input.num_nodes() == 0 {
-        return 0;
-    }
-
-    route_weights_iter(input)
-        .max()
-        .expect("at least one permutation, for non-empty graph")
+fn part2(input: &Graph<usize>) -> usize {
+    shortest_or_longest(input, Objective::Maximize)
 }
 
 fn main() -> Result<()> {
@@ -152,7 +81,7 @@ Dublin to Belfast = 141
     fn parse() {
         let result = super::parse(BufReader::new(TEST.as_bytes()));
         assert!(result.is_ok());
-        assert_eq!(3, result.unwrap().weights.len());
+        assert_eq!(3, result.unwrap().num_nodes());
     }
 
     #[test]
@@ -1,3 +1,5 @@
+use adv_code_2015::graph::hamiltonian::{Objective, Route, held_karp};
+use adv_code_2015::graph::{Direction, Graph, Storage};
 use adv_code_2015::start_day;
 use anyhow::{Result, anyhow};
 use const_format::concatcp;
@@ -7,14 +9,13 @@ use nom::bytes::complete::tag;
 use nom::character::complete::{alpha1, i32};
 use nom::combinator::all_consuming;
 use nom::{Finish, IResult, Parser};
-use std::collections::HashMap;
 use std::io::{BufRead, BufReader};
 use std::time::Instant;
 
 const DAY: &str = "13";
 const INPUT_FILE: &str = concatcp!("input/", DAY, ".txt");
 
-type Input = HashMap<(String, String), i32>;
+type Input = Graph<i32>;
 
 fn parse_line(s: &str) -> IResult<&str, (&str, &str, i32)> {
     all_consuming((
@@ -35,55 +36,52 @@ fn parse_line(s: &str) -> IResult<&str, (&str, &str, i32)> {
 }
 
 fn parse<R: BufRead>(reader: R) -> Result<Input> {
-    let mut result = HashMap::new();
+    let mut result = Graph::new(Direction::Directed, Storage::Dense);
     for res_l in reader.lines() {
         let line = res_l.map_err(|e| anyhow!("error reading line {e}"))?;
         let (_, (a, b, points)) = parse_line(line.as_str())
             .finish()
             .map_err(|e| anyhow!("parse error {e}"))?;
-        result.insert((a.to_owned(), b.to_owned()), points);
+        result.add_edge(a, b, points);
     }
 
     Ok(result)
 }
 
-fn solve<F>(input: &Input, windows: F) -> Result<i32>
-where
-    // yeah idk Claude had to help me out with this type
-    F: for<'a> Fn(&'a [&&str]) -> Box<dyn Iterator<Item = (&'a &'a str, &'a &'a str)> + 'a>,
-{
-    let guests: Vec<_> = input.keys().map(|(a, _)| a.as_str()).unique().collect();
-    let score_pair = |(&a, &b): (&&str, &&str)| -> Result<i32> {
-        let ab = input
-            .get(&(a.to_string(), b.to_string()))
-            .ok_or_else(|| anyhow!("missing edge {a} -> {b}"))?;
-        let ba = input
-            .get(&(b.to_string(), a.to_string()))
-            .ok_or_else(|| anyhow!("missing edge {b} -> {a}"))?;
-        Ok(ab + ba)
-    };
-    let max = guests
-        .iter()
-        .permutations(guests.len())
-        .map(|perm| windows(&perm).map(score_pair).sum::<Result<i32>>())
-        .collect::<Result<Vec<i32>>>()?
-        .into_iter()
-        .max()
-        .unwrap_or_default();
-    Ok(max)
+/// Undirected graph where each edge is the total happiness change of seating the pair together
+fn pair_scores(input: &Input) -> Result<Graph<i32>> {
+    let mut pairs = Graph::new(Direction::Undirected, Storage::Dense);
+    for a in input.node_ids() {
+        pairs.add_node(input.name(a).unwrap_or_default());
+    }
+    for (a, b) in input.node_ids().tuple_combinations() {
+        let missing = |a, b| {
+            anyhow!(
+                "missing edge {} -> {}",
+                input.name(a).unwrap_or_default(),
+                input.name(b).unwrap_or_default()
+            )
+        };
+        let ab = input.weight(a, b).ok_or_else(|| missing(a, b))?;
+        let ba = input.weight(b, a).ok_or_else(|| missing(b, a))?;
+        pairs.add_edge_by_id(a, b, ab + ba)?;
+    }
+    Ok(pairs)
+}
+
+fn solve(input: &Input, route: Route) -> Result<i32> {
+    let pairs = pair_scores(input)?;
+    Ok(held_karp(&pairs, Objective::Maximize, route, None).map_or(0, |tour| tour.score()))
 }
 
 fn part1(input: &Input) -> Result<i32> {
-    solve(input, |perm| {
-        Box::new(perm.iter().copied().circular_tuple_windows())
-    })
+    solve(input, Route::Cycle)
 }
 
 fn part2(input: &Input) -> Result<i32> {
-    // Rather than add an explicit "me", which would cause us to have 9x the permutations,
-    // don't consider the last <> first window (circular -> non-circular windows). This implicitly
-    // seats me between the first and last guests in the permutation.
-    solve(input, |perm| Box::new(perm.iter().copied().tuple_windows()))
+    // Rather than add an explicit "me", find the best open path. This implicitly seats me between
+    // the guests at either end, neither of whom gains or loses anything from it.
+    solve(input, Route::Path)
 }
 
 fn main() -> Result<()> {
@@ -135,20 +133,12 @@ David would gain 41 happiness units by sitting next to Carol.
         assert!(result.is_ok());
 
         let parsed = result.unwrap();
-        assert_eq!(12, parsed.len());
-
-        assert_eq!(
-            Some(-79),
-            parsed
-                .get(&("Alice".to_string(), "Carol".to_string()))
-                .copied()
-        );
-        assert_eq!(
-            Some(55),
-            parsed
-                .get(&("Carol".to_string(), "David".to_string()))
-                .copied()
-        );
+        assert_eq!(4, parsed.num_nodes());
+        assert_eq!(12, parsed.edges().count());
+
+        let id = |name| parsed.id(name).expect("guest exists");
+        assert_eq!(Some(-79), parsed.weight(id("Alice"), id("Carol")).copied());
+        assert_eq!(Some(55), parsed.weight(id("Carol"), id("David")).copied());
     }
 
     #[test]
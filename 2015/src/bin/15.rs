@@ -1,8 +1,8 @@
+use adv_code_2015::recipe::{Constraint, RecipeOptimizer};
 use adv_code_2015::start_day;
 use anyhow::Result;
 use anyhow::anyhow;
 use const_format::concatcp;
-use itertools::Itertools;
 use nom::Finish;
 use nom::IResult;
 use nom::Parser;
@@ -72,71 +72,49 @@ fn parse_line(s: &str) -> IResult<&str, Ingredient> {
     )
 }
 
-fn solve<F>(input: &Input, score: F) -> i32
-where
-    F: Fn(&[(&Ingredient, i32)]) -> i32,
-{
-    // Degenerate case: only 1 ingredient
-    if input.len() == 1 {
-        return score(&[(input.first().unwrap(), 100)]);
+impl Ingredient {
+    fn properties(&self) -> Vec<i64> {
+        [
+            self.capacity,
+            self.durability,
+            self.flavor,
+            self.texture,
+            self.calories,
+        ]
+        .into_iter()
+        .map(i64::from)
+        .collect()
     }
-
-    let mut amounts: Vec<i32> = Vec::with_capacity(input.len());
-    (0..=100)
-        .combinations_with_replacement(input.len() - 1)
-        .map(|mut v| {
-            amounts.clear();
-            v.sort_unstable();
-            // Each v_i represents at which "teaspoon" ingredient ends, exclusive.
-            // v must be nonempty by construction
-            #[allow(clippy::indexing_slicing)]
-            amounts.push(v[0]);
-            amounts.extend(v.iter().tuple_windows().map(|(&s, &e)| e - s));
-            amounts.push(100 - amounts.iter().sum::<i32>());
-
-            score(
-                &input
-                    .iter()
-                    .zip(amounts.iter().copied())
-                    .collect::<Vec<_>>(),
-            )
-        })
-        .max()
-        .unwrap_or(0)
 }
 
-fn score_and_calories(recipe: &[(&Ingredient, i32)]) -> (i32, i32) {
-    let (cap, dur, fla, tex, cal) = recipe.iter().fold(
-        (0, 0, 0, 0, 0),
-        |(cap, dur, fla, tex, cal), &(i, amount)| {
-            (
-                cap + i.capacity * amount,
-                dur + i.durability * amount,
-                fla + i.flavor * amount,
-                tex + i.texture * amount,
-                cal + i.calories * amount,
-            )
-        },
-    );
-
-    (cap.max(0) * dur.max(0) * fla.max(0) * tex.max(0), cal)
-}
+/// Every property but calories counts towards the score
+const SCORED: [usize; 4] = [0, 1, 2, 3];
+const CALORIES: usize = 4;
+const TEASPOONS: u32 = 100;
 
-fn p1_score(recipe: &[(&Ingredient, i32)]) -> i32 {
-    score_and_calories(recipe).0
+fn optimizer(input: &Input) -> Result<RecipeOptimizer> {
+    RecipeOptimizer::new(
+        input.iter().map(Ingredient::properties).collect(),
+        TEASPOONS,
+        SCORED.to_vec(),
+    )
 }
 
-fn p2_score(recipe: &[(&Ingredient, i32)]) -> i32 {
-    let (score, cal) = score_and_calories(recipe);
-    if cal == 500 { score } else { 0 }
+fn best_score(optimizer: &RecipeOptimizer) -> Result<u128> {
+    Ok(optimizer
+        .best()?
+        .ok_or_else(|| anyhow!("no recipe meets the constraints"))?
+        .score)
 }
 
-fn part1(input: &Input) -> i32 {
-    solve(input, p1_score)
+fn part1(input: &Input) -> Result<u128> {
+    best_score(&optimizer(input)?)
 }
 
-fn part2(input: &Input) -> i32 {
-    solve(input, p2_score)
+fn part2(input: &Input) -> Result<u128> {
+    let mut optimizer = optimizer(input)?;
+    optimizer.add_constraint(Constraint::equal(CALORIES, 500))?;
+    best_score(&optimizer)
 }
 
 fn main() -> Result<()> {
@@ -150,13 +128,13 @@ fn main() -> Result<()> {
 
     println!("=== Part 1 ===");
     let p1_time = Instant::now();
-    let result = part1(&input);
+    let result = part1(&input)?;
     println!("Result = {result}");
     println!("Elapsed = {:.2?}", p1_time.elapsed());
 
     println!("\n=== Part 2 ===");
     let p2_time = Instant::now();
-    let result = part2(&input);
+    let result = part2(&input)?;
     println!("Result = {result}");
     println!("Elapsed = {:.2?}", p2_time.elapsed());
 
@@ -207,7 +185,7 @@ Cinnamon: capacity 2, durability 3, flavor -2, texture -1, calories 3
     fn part_1() {
         let expected = 62842880;
         let input = super::parse(BufReader::new(TEST.as_bytes())).expect("parse succeeds");
-        let result = part1(&input);
+        let result = part1(&input).expect("succeeds");
         assert_eq!(result, expected)
     }
 
@@ -215,7 +193,7 @@ Cinnamon: capacity 2, durability 3, flavor -2, texture -1, calories 3
     fn part_2() {
         let expected = 57600000;
         let input = super::parse(BufReader::new(TEST.as_bytes())).expect("parse succeeds");
-        let result = part2(&input);
+        let result = part2(&input).expect("succeeds");
         assert_eq!(result, expected)
     }
 }
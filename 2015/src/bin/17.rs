@@ -1,3 +1,4 @@
+use adv_code_2015::memo::Memo;
 use adv_code_2015::start_day;
 use anyhow::{Result, anyhow};
 use const_format::concatcp;
@@ -5,7 +6,6 @@ use nom::character::complete::{line_ending, u16};
 use nom::multi::separated_list0;
 use nom::{Finish, Parser};
 use std::cmp::Ordering;
-use std::collections::HashMap;
 use std::time::Instant;
 
 const DAY: &str = "17";
@@ -19,39 +19,36 @@ fn parse(input: &str) -> Result<Vec<u16>> {
     Ok(values)
 }
 
-fn num_solutions<'a>(
-    buckets: &'a [u16],
+/// Ways to make `target` from the buckets from `idx` onwards
+fn num_solutions(
+    buckets: &[u16],
+    idx: usize,
     target: u16,
-    memo: &mut HashMap<(&'a [u16], u16), usize>,
+    memo: &mut Memo<(usize, u16), usize>,
 ) -> usize {
     if target == 0 {
         // If the target is 0, there is exactly one way to get it (take empty set)
         return 1;
     }
 
-    if let Some(&cached) = memo.get(&(buckets, target)) {
-        return cached;
-    }
-
-    match buckets.split_first() {
-        // No buckets left and nonzero target -> no ways to get there
-        None => 0,
-        Some((&next_bucket, rest)) => {
-            let mut total = 0;
-            if next_bucket <= target {
-                // Can fill next_bucket, do so
-                total += num_solutions(rest, target - next_bucket, memo);
-            }
-            // Also consider case where we don't use next_bucket
-            total += num_solutions(rest, target, memo);
-            memo.insert((buckets, target), total);
-            total
+    // No buckets left and nonzero target -> no ways to get there
+    let Some(&next_bucket) = buckets.get(idx) else {
+        return 0;
+    };
+    memo.get_or_compute((idx, target), |memo| {
+        let mut total = 0;
+        if next_bucket <= target {
+            // Can fill next_bucket, do so
+            total += num_solutions(buckets, idx + 1, target - next_bucket, memo);
         }
-    }
+        // Also consider case where we don't use next_bucket
+        total + num_solutions(buckets, idx + 1, target, memo)
+    })
 }
 
 fn part1(input: &[u16], target: u16) -> usize {
-    num_solutions(input, target, &mut HashMap::with_capacity(input.len()))
+    let mut memo = Memo::with_capacity(input.len() * usize::from(target));
+    num_solutions(input, 0, target, &mut memo)
 }
 
 // Each tuple (n, m) represents finding n ways to some solution using exactly m buckets
@@ -71,43 +68,39 @@ fn merge(a: Option<MinWays>, b: Option<MinWays>) -> Option<MinWays> {
     }
 }
 
-fn ways_to_min_buckets<'a>(
-    buckets: &'a [u16],
+/// Fewest buckets from `idx` onwards that make `target`, and the number of ways of doing so
+fn ways_to_min_buckets(
+    buckets: &[u16],
+    idx: usize,
     target: u16,
-    memo: &mut HashMap<(&'a [u16], u16), Option<MinWays>>,
+    memo: &mut Memo<(usize, u16), Option<MinWays>>,
 ) -> Option<MinWays> {
     if target == 0 {
         // If the target is 0 we get **1** solution by taking **0** buckets
         return Some((1, 0));
     }
 
-    if let Some(&cached) = memo.get(&(buckets, target)) {
-        return cached;
-    }
-
-    match buckets.split_first() {
-        // No buckets left and nonzero target, no way to a solution
-        None => None,
-        Some((&next_bucket, rest)) => {
-            let mut min_so_far = None;
-            if next_bucket <= target {
-                // Can fill bucket i, do so
-                if let Some((ways, buckets)) = ways_to_min_buckets(rest, target - next_bucket, memo)
-                {
-                    min_so_far = Some((ways, buckets + 1));
-                }
+    // No buckets left and nonzero target, no way to a solution
+    let &next_bucket = buckets.get(idx)?;
+    memo.get_or_compute((idx, target), |memo| {
+        let mut min_so_far = None;
+        if next_bucket <= target {
+            // Can fill bucket i, do so
+            if let Some((ways, used)) =
+                ways_to_min_buckets(buckets, idx + 1, target - next_bucket, memo)
+            {
+                min_so_far = Some((ways, used + 1));
             }
-            // Also consider case where we don't use bucket i
-            let without_bucket = ways_to_min_buckets(rest, target, memo);
-            min_so_far = merge(min_so_far, without_bucket);
-            memo.insert((buckets, target), min_so_far);
-            min_so_far
         }
-    }
+        // Also consider case where we don't use bucket i
+        let without_bucket = ways_to_min_buckets(buckets, idx + 1, target, memo);
+        merge(min_so_far, without_bucket)
+    })
 }
 
 fn part2(input: &[u16], target: u16) -> usize {
-    match ways_to_min_buckets(input, target, &mut HashMap::with_capacity(input.len())) {
+    let mut memo = Memo::with_capacity(input.len() * usize::from(target));
+    match ways_to_min_buckets(input, 0, target, &mut memo) {
         None => 0,
         Some((ways, _)) => ways,
     }
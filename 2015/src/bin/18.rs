@@ -1,3 +1,4 @@
+use adv_code_2015::bit_grid::BitGrid;
 use adv_code_2015::grids::Grid;
 use adv_code_2015::grids::Position;
 use adv_code_2015::start_day;
@@ -27,38 +28,50 @@ fn parse(input: &str) -> Result<Grid<bool>> {
     Grid::new(values)
 }
 
-fn next_state(pos: &Position, state: &Grid<bool>) -> Option<bool> {
-    let on_neighbors = pos
-        .adjacent()
-        .map(|nbr| state.get(&nbr))
-        .filter(|opt| opt.is_some_and(|&b| b))
-        .count();
-
-    match state.get(pos) {
-        None => None,
-        Some(true) => Some(on_neighbors == 2 || on_neighbors == 3),
-        Some(false) => Some(on_neighbors == 3),
+const NEIGHBOR_OFFSETS: [(isize, isize); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
+fn next_step(curr: &BitGrid) -> BitGrid {
+    // Count neighbors for 64 cells at once with a bit-sliced counter. `ones` and `twos` hold the
+    // low bits of each cell's count, `many` is set once a count reaches four
+    let mut ones = BitGrid::new(curr.num_rows(), curr.num_cols());
+    let mut twos = ones.clone();
+    let mut many = ones.clone();
+    for (rows, cols) in NEIGHBOR_OFFSETS {
+        let neighbor = curr.shifted(rows, cols);
+        let mut carry = neighbor.clone();
+        carry.and_assign(&ones).expect("grids are the same size");
+        ones.xor_assign(&neighbor).expect("grids are the same size");
+
+        let mut overflow = carry.clone();
+        overflow.and_assign(&twos).expect("grids are the same size");
+        twos.xor_assign(&carry).expect("grids are the same size");
+        many.or_assign(&overflow).expect("grids are the same size");
     }
-}
 
-fn next_step(initial: &Grid<bool>, buf: &mut Grid<bool>) -> Result<()> {
-    for pos in initial.all_positions() {
-        buf.set(
-            &pos,
-            next_state(&pos, initial).expect("pos references valid position in initial"),
-        )?;
-    }
-    Ok(())
+    // On with exactly three neighbors, or with two if already on
+    let mut next = ones;
+    next.or_assign(curr).expect("grids are the same size");
+    next.and_assign(&twos).expect("grids are the same size");
+    next.combine(&many, |n, m| n & !m)
+        .expect("grids are the same size");
+    next
 }
 
 fn part1(initial: &Grid<bool>, num_steps: usize) -> usize {
-    let mut curr = initial.clone();
-    let mut next = initial.clone();
+    let mut curr = BitGrid::from(initial);
     for _ in 0..num_steps {
-        next_step(&curr, &mut next).expect("next and curr are same size");
-        (curr, next) = (next, curr);
+        curr = next_step(&curr);
     }
-    curr.values().filter(|&&v| v).count()
+    curr.count_ones()
 }
 
 fn part2(initial: &Grid<bool>, num_steps: usize) -> usize {
@@ -68,19 +81,17 @@ fn part2(initial: &Grid<bool>, num_steps: usize) -> usize {
         Position::new(initial.num_rows() - 1, 0),
         Position::new(initial.num_rows() - 1, initial.num_cols() - 1),
     ];
-    let mut curr = initial.clone();
+    let mut curr = BitGrid::from(initial);
     for corner in &corners {
         curr.set(corner, true).expect("corner is valid coordinate");
     }
-    let mut next = initial.clone();
     for _ in 0..num_steps {
-        next_step(&curr, &mut next).expect("next and curr are same size");
-        (curr, next) = (next, curr);
+        curr = next_step(&curr);
         for corner in &corners {
             curr.set(corner, true).expect("corner is valid coordinate");
         }
     }
-    curr.values().filter(|&&v| v).count()
+    curr.count_ones()
 }
 
 fn main() -> Result<()> {
@@ -1,3 +1,4 @@
+use adv_code_2015::grammar::Grammar;
 use adv_code_2015::start_day;
 use anyhow::{Result, anyhow};
 use const_format::concatcp;
@@ -50,39 +51,31 @@ fn part1(replacements: &Replacements, molecule: &str) -> Result<usize> {
     Ok(next_molecules(replacements, molecule)?.len())
 }
 
-fn part2(replacements: &Replacements, target: &str) -> Result<usize> {
-    // This one is tricky. First I tried doing something like BFS, but it blows up way too quickly.
-    // So the second solution (which works on my input) is to instead do it greedily, working backwards.
-    // Take all the X => Y replacements, at every step replace the longest such Y that appears in our
-    // string with its corresponding X. Repeat this until we get the initial "e" string, or we can't
-    // do any more steps.
-    // This happens to work for my input but it certainly is not a general solution that would work for
-    // all possible replacements/goal strings. It probably works for all the AoC inputs, though, due to
-    // some cleverness in the problem formulation.
-    let mut inverted: Vec<_> = replacements
-        .iter()
-        .flat_map(|(&k, vs)| vs.iter().map(move |&v| (v, k)))
-        .collect();
-    inverted.sort_by_key(|(v, _)| std::cmp::Reverse(v.len()));
-
-    let mut curr = target.to_string();
-    let mut num_replacements = 0;
-    while curr != "e" {
-        let mut did_replacement = false;
-        for (long, short) in &inverted {
-            if curr.contains(long) {
-                curr = curr.replacen(long, short, 1);
-                num_replacements += 1;
-                did_replacement = true;
-                break;
-            }
-        }
-        if !did_replacement {
-            return Err(anyhow!("did not find way create given target"));
+/// Split a molecule into elements, each a capital letter followed by any lower case ones. A lone
+/// `e` counts as an element too.
+fn elements(molecule: &str) -> Vec<&str> {
+    let mut result = Vec::new();
+    let mut start = 0;
+    for (i, c) in molecule.char_indices().skip(1) {
+        if c.is_ascii_uppercase() {
+            result.extend(molecule.get(start..i));
+            start = i;
         }
     }
+    result.extend(molecule.get(start..).filter(|rest| !rest.is_empty()));
+    result
+}
 
-    Ok(num_replacements)
+fn part2(replacements: &Replacements, target: &str) -> Result<u64> {
+    // Each replacement rewrites a single element, so they form a context free grammar and the
+    // fewest steps is the cheapest parse of the target from "e"
+    let mut grammar = Grammar::new();
+    for (&from, tos) in replacements {
+        for &to in tos {
+            grammar.add_rule(from, &elements(to))?;
+        }
+    }
+    grammar.min_derivation_steps("e", &elements(target))
 }
 
 fn main() -> Result<()> {
@@ -151,5 +144,13 @@ HOH
         let (replacements, _) = super::parse(TEST).expect("parse succeeds");
         assert_eq!(3, part2(&replacements, "HOH").expect("succeeds"));
         assert_eq!(6, part2(&replacements, "HOHOHO").expect("succeeds"));
+        assert!(part2(&replacements, "OOO").is_err());
+    }
+
+    #[test]
+    fn split_elements() {
+        assert_eq!(vec!["Ca", "Rn", "F", "Ar", "H"], elements("CaRnFArH"));
+        assert_eq!(vec!["e"], elements("e"));
+        assert!(elements("").is_empty());
     }
 }
@@ -1,10 +1,11 @@
 use adv_code_2015::start_day;
+use adv_code_common::search::{SearchState, Solution, dijkstra};
 use anyhow::{Result, anyhow};
-use std::{collections::VecDeque, time::Instant};
+use std::time::Instant;
 
 const DAY: &str = "22";
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 enum Spell {
     MagicMissile,
     Drain,
@@ -82,7 +83,7 @@ impl Spell {
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Eq, Hash)]
 enum Effect {
     Poison(usize),
     Shield(usize),
@@ -120,7 +121,7 @@ impl Effect {
     }
 }
 
-#[derive(Clone, Default)]
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
 struct Stats {
     hit_points: u16,
     mana: u16,
@@ -136,6 +137,7 @@ impl Stats {
     }
 }
 
+#[derive(Clone)]
 enum Mode {
     Normal,
     Hard,
@@ -170,61 +172,62 @@ fn play_round(spell: &Spell, player: &Stats, boss: &Stats, mode: &Mode) -> Resul
     Ok((next_player, next_boss))
 }
 
-fn best_solution(player: &Stats, boss: &Stats, mode: &Mode) -> Option<u16> {
-    struct GameState {
-        mana_used: u16,
-        player: Stats,
-        boss: Stats,
-    }
+struct GameState {
+    player: Stats,
+    boss: Stats,
+    mode: Mode,
+}
 
-    let mut queue = VecDeque::new();
-    queue.push_back(GameState {
-        mana_used: 0,
-        player: player.to_owned(),
-        boss: boss.to_owned(),
-    });
-
-    let mut best_so_far: Option<u16> = None;
-    while let Some(GameState {
-        mana_used,
-        player,
-        boss,
-    }) = queue.pop_front()
-    {
-        // Don't keep going if we've otherwise found a way to a better answer
-        if let Some(best) = best_so_far
-            && best <= mana_used
-        {
-            continue;
-        }
+impl SearchState for GameState {
+    type Move = Spell;
+    type Key = (Stats, Stats);
+
+    fn successors(&self) -> impl Iterator<Item = (Spell, u64, Self)> {
+        let in_play = self.boss.hit_points > 0 && self.player.hit_points > 0;
+        ALL_SPELLS
+            .iter()
+            .filter(move |_| in_play)
+            .filter_map(|spell| {
+                let (player, boss) =
+                    play_round(spell, &self.player, &self.boss, &self.mode).ok()?;
+                let next = Self {
+                    player,
+                    boss,
+                    mode: self.mode.clone(),
+                };
+                Some((spell.clone(), u64::from(spell.cost()), next))
+            })
+    }
 
-        if boss.hit_points == 0 {
-            best_so_far = Some(mana_used);
-            continue;
-        } else if player.hit_points == 0 {
-            continue;
-        }
+    fn is_goal(&self) -> bool {
+        self.boss.hit_points == 0
+    }
 
-        for spell in &ALL_SPELLS {
-            if let Ok((next_player, next_boss)) = play_round(spell, &player, &boss, mode) {
-                queue.push_back(GameState {
-                    mana_used: mana_used + spell.cost(),
-                    player: next_player,
-                    boss: next_boss,
-                });
-            }
-        }
+    fn key(&self) -> Option<Self::Key> {
+        Some((self.player.clone(), self.boss.clone()))
     }
+}
 
-    best_so_far
+/// Cheapest sequence of spells that defeats the boss
+fn best_solution(player: &Stats, boss: &Stats, mode: Mode) -> Option<Solution<Spell>> {
+    let start = GameState {
+        player: player.to_owned(),
+        boss: boss.to_owned(),
+        mode,
+    };
+    dijkstra(start).solution
+}
+
+fn least_mana(player: &Stats, boss: &Stats, mode: Mode) -> Option<u16> {
+    best_solution(player, boss, mode).and_then(|s| u16::try_from(s.cost()).ok())
 }
 
 fn part1(player: &Stats, boss: &Stats) -> Option<u16> {
-    best_solution(player, boss, &Mode::Normal)
+    least_mana(player, boss, Mode::Normal)
 }
 
 fn part2(player: &Stats, boss: &Stats) -> Option<u16> {
-    best_solution(player, boss, &Mode::Hard)
+    least_mana(player, boss, Mode::Hard)
 }
 
 fn main() -> Result<()> {
@@ -290,5 +293,17 @@ mod tests {
             ..Stats::default()
         };
         assert_eq!(part1(&player, &boss), Some(641));
+
+        let spells = best_solution(&player, &boss, Mode::Normal).expect("boss can be beaten");
+        assert_eq!(
+            &[
+                Spell::Recharge,
+                Spell::Shield,
+                Spell::Drain,
+                Spell::Poison,
+                Spell::MagicMissile
+            ],
+            spells.moves()
+        );
     }
 }
@@ -1,99 +1,58 @@
+use adv_code_2015::partition::balanced_partition;
 use adv_code_2015::start_day;
 use anyhow::{Result, anyhow};
 use const_format::concatcp;
-use nom::character::complete::{line_ending, usize as nom_usize};
+use nom::character::complete::{line_ending, u64 as nom_u64};
 use nom::multi::separated_list0;
 use nom::{Finish, Parser};
-use std::cmp::Ordering;
-use std::collections::HashMap;
 use std::time::Instant;
 
 const DAY: &str = "24";
 const INPUT_FILE: &str = concatcp!("input/", DAY, ".txt");
 
-fn parse(input: &str) -> Result<Vec<usize>> {
-    let (_, packages) = separated_list0(line_ending, nom_usize)
+fn parse(input: &str) -> Result<Vec<u64>> {
+    let (_, packages) = separated_list0(line_ending, nom_u64)
         .parse(input)
         .finish()
         .map_err(|e: nom::error::Error<_>| anyhow!("parsing error {e}"))?;
     Ok(packages)
 }
 
-fn better_solution(a: Vec<usize>, b: Vec<usize>) -> Vec<usize> {
-    match a.len().cmp(&b.len()) {
-        Ordering::Less => a,
-        Ordering::Greater => b,
-        Ordering::Equal => {
-            let qe_a = a.iter().product::<usize>();
-            let qe_b = b.iter().product();
-            match qe_a.cmp(&qe_b) {
-                Ordering::Less | Ordering::Equal => a,
-                Ordering::Greater => b,
-            }
-        }
-    }
+fn quantum_entanglement(group: &[u64]) -> u128 {
+    group.iter().map(|&p| u128::from(p)).product()
 }
 
-fn solve<'a>(
-    target: usize,
-    packages: &'a [usize],
-    memo: &mut HashMap<(usize, &'a [usize]), Vec<usize>>,
-) -> Result<Vec<usize>> {
-    if target == 0 {
-        return Ok(vec![]);
-    }
-
-    if let Some(v) = memo.get(&(target, packages)) {
-        return Ok(v.clone());
-    }
-
-    let best = match packages.split_first() {
-        None => Err(anyhow!("did not find way to make {target}")),
-        Some((p, rest)) if *p > target => solve(target, rest, memo),
-        Some((p, rest)) => {
-            // Implicit else: p <= target
-            let with_p = solve(target - p, rest, memo).map(|mut v| {
-                v.push(*p);
-                v
-            });
-            let without_p = solve(target, rest, memo);
-            match (with_p, without_p) {
-                (Err(_), Err(_)) => Err(anyhow!("did not find way to make {target}")),
-                (a, Err(_)) => a,
-                (Err(_), b) => b,
-                (Ok(sol_a), Ok(sol_b)) => Ok(better_solution(sol_a, sol_b)),
-            }
-        }
-    }?;
-
-    memo.insert((target, packages), best.clone());
-    Ok(best)
+/// Split the packages into `groups` groups of equal weight, the first, for the passenger
+/// compartment, having the fewest packages and then the least quantum entanglement
+fn solve(packages: &[u64], groups: usize) -> Result<Vec<Vec<u64>>> {
+    let weights = |group: &[usize]| -> Vec<u64> {
+        group
+            .iter()
+            .map(|&i| *packages.get(i).expect("index in range by construction"))
+            .collect()
+    };
+    let split = balanced_partition(packages, groups, |group| {
+        quantum_entanglement(&weights(group))
+    })?
+    .ok_or_else(|| anyhow!("given packages cannot be divided into {groups} even groups"))?;
+    Ok(split.iter().map(|group| weights(group)).collect())
 }
 
-fn part1(input: &[usize]) -> Result<usize> {
-    let total = input.iter().sum::<usize>();
-    if !total.is_multiple_of(3) {
-        return Err(anyhow!(
-            "given packages cannot be divided into three even groups"
-        ));
-    }
+fn part1(input: &[u64]) -> Result<Vec<Vec<u64>>> {
+    solve(input, 3)
+}
 
-    let group_total = total / 3;
-    let solution = solve(group_total, input, &mut HashMap::new())?;
-    Ok(solution.iter().product())
+fn part2(input: &[u64]) -> Result<Vec<Vec<u64>>> {
+    solve(input, 4)
 }
 
-fn part2(input: &[usize]) -> Result<usize> {
-    let total = input.iter().sum::<usize>();
-    if !total.is_multiple_of(4) {
-        return Err(anyhow!(
-            "given packages cannot be divided into four even groups"
-        ));
+fn report(groups: &[Vec<u64>]) -> Result<()> {
+    let first = groups.first().ok_or_else(|| anyhow!("no groups"))?;
+    for group in groups {
+        println!("Group = {group:?}");
     }
-
-    let group_total = total / 4;
-    let solution = solve(group_total, input, &mut HashMap::new())?;
-    Ok(solution.iter().product())
+    println!("Result = {}", quantum_entanglement(first));
+    Ok(())
 }
 
 fn main() -> Result<()> {
@@ -107,14 +66,12 @@ fn main() -> Result<()> {
 
     println!("=== Part 1 ===");
     let p1_time = Instant::now();
-    let result = part1(&input)?;
-    println!("Result = {result}");
+    report(&part1(&input)?)?;
     println!("Elapsed = {:.2?}", p1_time.elapsed());
 
     println!("\n=== Part 2 ===");
     let p2_time = Instant::now();
-    let result = part2(&input)?;
-    println!("Result = {result}");
+    report(&part2(&input)?)?;
     println!("Elapsed = {:.2?}", p2_time.elapsed());
 
     Ok(())
@@ -149,7 +106,10 @@ mod tests {
     fn part_1() {
         let expected = 99;
         let input = super::parse(TEST).expect("parse succeeds");
-        let result = part1(&input).expect("succeeds");
+        let groups = part1(&input).expect("succeeds");
+        assert_eq!(Some(&vec![9, 11]), groups.first());
+        assert!(groups.iter().all(|g| g.iter().sum::<u64>() == 20));
+        let result = quantum_entanglement(groups.first().expect("some group"));
         assert_eq!(result, expected)
     }
 
@@ -157,7 +117,15 @@ mod tests {
     fn part_2() {
         let expected = 44;
         let input = super::parse(TEST).expect("parse succeeds");
-        let result = part2(&input).expect("succeeds");
+        let groups = part2(&input).expect("succeeds");
+        assert_eq!(4, groups.len());
+        assert!(groups.iter().all(|g| g.iter().sum::<u64>() == 15));
+        let result = quantum_entanglement(groups.first().expect("some group"));
         assert_eq!(result, expected)
     }
+
+    #[test]
+    fn uneven() {
+        assert!(solve(&[1, 2, 3, 5], 3).is_err());
+    }
 }
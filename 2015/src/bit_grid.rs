@@ -0,0 +1,336 @@
+use crate::grids::{Grid, Position};
+use anyhow::{Result, anyhow};
+use std::ops::Range;
+
+const WORD_BITS: usize = u64::BITS as usize;
+
+/// Rectangular grid of booleans packed 64 to a word.
+///
+/// Each row starts on a fresh word, so whole-row operations work a word at a time. Bits past the
+/// last column of a row are always kept clear.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BitGrid {
+    num_rows: usize,
+    num_cols: usize,
+    words_per_row: usize,
+    words: Vec<u64>,
+}
+
+impl BitGrid {
+    /// Grid of the given size with every cell off
+    #[must_use]
+    pub fn new(num_rows: usize, num_cols: usize) -> Self {
+        let words_per_row = num_cols.div_ceil(WORD_BITS);
+        Self {
+            num_rows,
+            num_cols,
+            words_per_row,
+            words: vec![0; num_rows * words_per_row],
+        }
+    }
+
+    #[must_use]
+    pub const fn num_rows(&self) -> usize {
+        self.num_rows
+    }
+
+    #[must_use]
+    pub const fn num_cols(&self) -> usize {
+        self.num_cols
+    }
+
+    #[must_use]
+    pub fn get(&self, p: &Position) -> Option<bool> {
+        if p.col() >= self.num_cols {
+            return None;
+        }
+        let word = self.row_words(p.row())?.get(p.col() / WORD_BITS)?;
+        Some(word & (1 << (p.col() % WORD_BITS)) != 0)
+    }
+
+    /// Set value of given position to given value
+    /// # Errors
+    /// If given position is not a valid position in grid
+    pub fn set(&mut self, p: &Position, val: bool) -> Result<()> {
+        if p.col() >= self.num_cols {
+            return Err(anyhow!("invalid position given to set"));
+        }
+        let word = self
+            .row_words_mut(p.row())
+            .and_then(|row| row.get_mut(p.col() / WORD_BITS))
+            .ok_or_else(|| anyhow!("invalid position given to set"))?;
+        let mask = 1 << (p.col() % WORD_BITS);
+        if val {
+            *word |= mask;
+        } else {
+            *word &= !mask;
+        }
+        Ok(())
+    }
+
+    /// Set every cell in `cols` of the given row to `val`
+    /// # Errors
+    /// If the row or range of columns is out of bounds
+    pub fn fill_range(&mut self, row: usize, cols: Range<usize>, val: bool) -> Result<()> {
+        if cols.end > self.num_cols {
+            return Err(anyhow!("column range {cols:?} out of bounds"));
+        }
+        let words = self
+            .row_words_mut(row)
+            .ok_or_else(|| anyhow!("row {row} out of bounds"))?;
+        for (idx, word) in words.iter_mut().enumerate() {
+            let mask = range_mask(idx, &cols);
+            if val {
+                *word |= mask;
+            } else {
+                *word &= !mask;
+            }
+        }
+        Ok(())
+    }
+
+    /// Number of cells that are on
+    #[must_use]
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    /// Number of cells in the given row that are on
+    #[must_use]
+    pub fn row_count_ones(&self, row: usize) -> Option<usize> {
+        self.row_words(row)
+            .map(|words| words.iter().map(|w| w.count_ones() as usize).sum())
+    }
+
+    /// The packed words of a row, column `c` being bit `c % 64` of word `c / 64`
+    #[must_use]
+    pub fn row_words(&self, row: usize) -> Option<&[u64]> {
+        let start = row.checked_mul(self.words_per_row)?;
+        self.words.get(start..start + self.words_per_row)
+    }
+
+    fn row_words_mut(&mut self, row: usize) -> Option<&mut [u64]> {
+        let start = row.checked_mul(self.words_per_row)?;
+        self.words.get_mut(start..start + self.words_per_row)
+    }
+
+    /// Cell-wise and with another grid of the same size
+    /// # Errors
+    /// If the grids differ in size
+    pub fn and_assign(&mut self, other: &Self) -> Result<()> {
+        self.combine(other, |a, b| a & b)
+    }
+
+    /// Cell-wise or with another grid of the same size
+    /// # Errors
+    /// If the grids differ in size
+    pub fn or_assign(&mut self, other: &Self) -> Result<()> {
+        self.combine(other, |a, b| a | b)
+    }
+
+    /// Cell-wise xor with another grid of the same size
+    /// # Errors
+    /// If the grids differ in size
+    pub fn xor_assign(&mut self, other: &Self) -> Result<()> {
+        self.combine(other, |a, b| a ^ b)
+    }
+
+    /// Apply `f` word by word with another grid of the same size. `f` must map clear bits in both
+    /// inputs to a clear bit so padding stays clear.
+    /// # Errors
+    /// If the grids differ in size
+    pub fn combine<F>(&mut self, other: &Self, f: F) -> Result<()>
+    where
+        F: Fn(u64, u64) -> u64,
+    {
+        if (self.num_rows, self.num_cols) != (other.num_rows, other.num_cols) {
+            return Err(anyhow!("grids differ in size"));
+        }
+        for (a, &b) in self.words.iter_mut().zip(&other.words) {
+            *a = f(*a, b);
+        }
+        Ok(())
+    }
+
+    /// Copy of the grid moved by the given number of rows (down) and columns (right). Cells moved
+    /// off the edge are dropped and cells moved in are off. Cell (r, c) of the result holds cell
+    /// (r - rows, c - cols) of the original, so for example `shifted(1, 0)` lines up each cell
+    /// with its neighbor above.
+    #[must_use]
+    pub fn shifted(&self, rows: isize, cols: isize) -> Self {
+        let mut result = Self::new(self.num_rows, self.num_cols);
+        for row in 0..self.num_rows {
+            let (Some(src_row), Some(dst)) = (
+                row.checked_add_signed(-rows)
+                    .and_then(|src_row| self.row_words(src_row)),
+                result.row_words_mut(row),
+            ) else {
+                continue;
+            };
+            shift_words(src_row, dst, cols);
+        }
+        result.clear_padding();
+        result
+    }
+
+    fn clear_padding(&mut self) {
+        let used_bits = self.num_cols % WORD_BITS;
+        if used_bits == 0 {
+            return;
+        }
+        let mask = (1 << used_bits) - 1;
+        for row in self.words.chunks_mut(self.words_per_row) {
+            if let Some(last) = row.last_mut() {
+                *last &= mask;
+            }
+        }
+    }
+}
+
+impl From<&Grid<bool>> for BitGrid {
+    fn from(grid: &Grid<bool>) -> Self {
+        let mut result = Self::new(grid.num_rows(), grid.num_cols());
+        for pos in grid.all_positions() {
+            if grid.get(&pos).is_some_and(|&b| b) {
+                result.set(&pos, true).expect("grids are the same size");
+            }
+        }
+        result
+    }
+}
+
+/// Mask of the bits of word `idx` that fall in the column range
+fn range_mask(idx: usize, cols: &Range<usize>) -> u64 {
+    let word_start = idx * WORD_BITS;
+    let lo = cols.start.clamp(word_start, word_start + WORD_BITS) - word_start;
+    let hi = cols.end.clamp(word_start, word_start + WORD_BITS) - word_start;
+    if lo >= hi {
+        return 0;
+    }
+    let below_hi = if hi == WORD_BITS {
+        u64::MAX
+    } else {
+        (1 << hi) - 1
+    };
+    below_hi & !((1 << lo) - 1)
+}
+
+/// Write `src` moved `cols` bits towards higher columns into `dst`
+fn shift_words(src: &[u64], dst: &mut [u64], cols: isize) {
+    let word_offset = cols.unsigned_abs() / WORD_BITS;
+    let bit_offset = cols.unsigned_abs() % WORD_BITS;
+    let word_at = |idx: Option<usize>| idx.and_then(|i| src.get(i)).copied().unwrap_or(0);
+
+    for (idx, word) in dst.iter_mut().enumerate() {
+        *word = if cols >= 0 {
+            let main = word_at(idx.checked_sub(word_offset));
+            let carry = word_at(idx.checked_sub(word_offset + 1));
+            if bit_offset == 0 {
+                main
+            } else {
+                (main << bit_offset) | (carry >> (WORD_BITS - bit_offset))
+            }
+        } else {
+            let main = word_at(Some(idx + word_offset));
+            let carry = word_at(Some(idx + word_offset + 1));
+            if bit_offset == 0 {
+                main
+            } else {
+                (main >> bit_offset) | (carry << (WORD_BITS - bit_offset))
+            }
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bit_grid::BitGrid;
+    use crate::grids::{Grid, Position};
+
+    fn to_strings(grid: &BitGrid) -> Vec<String> {
+        (0..grid.num_rows())
+            .map(|r| {
+                (0..grid.num_cols())
+                    .map(|c| match grid.get(&Position::new(r, c)) {
+                        Some(true) => '#',
+                        _ => '.',
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_get_set() {
+        let mut grid = BitGrid::new(3, 100);
+        assert!(grid.set(&Position::new(1, 70), true).is_ok());
+        assert_eq!(Some(true), grid.get(&Position::new(1, 70)));
+        assert_eq!(Some(false), grid.get(&Position::new(1, 69)));
+        assert_eq!(None, grid.get(&Position::new(1, 100)));
+        assert_eq!(None, grid.get(&Position::new(3, 0)));
+        assert!(grid.set(&Position::new(0, 100), true).is_err());
+        assert_eq!(1, grid.count_ones());
+    }
+
+    #[test]
+    fn test_fill_range() {
+        let mut grid = BitGrid::new(2, 200);
+        grid.fill_range(0, 10..150, true).expect("succeeds");
+        assert_eq!(Some(140), grid.row_count_ones(0));
+        grid.fill_range(0, 60..70, false).expect("succeeds");
+        assert_eq!(Some(130), grid.row_count_ones(0));
+        assert_eq!(Some(0), grid.row_count_ones(1));
+        assert!(grid.fill_range(1, 0..201, true).is_err());
+    }
+
+    #[test]
+    fn test_combine() {
+        let mut a = BitGrid::new(1, 4);
+        a.fill_range(0, 0..2, true).expect("succeeds");
+        let mut b = BitGrid::new(1, 4);
+        b.fill_range(0, 1..3, true).expect("succeeds");
+
+        let mut and = a.clone();
+        and.and_assign(&b).expect("succeeds");
+        assert_eq!(vec![".#.."], to_strings(&and));
+        let mut or = a.clone();
+        or.or_assign(&b).expect("succeeds");
+        assert_eq!(vec!["###."], to_strings(&or));
+        let mut xor = a.clone();
+        xor.xor_assign(&b).expect("succeeds");
+        assert_eq!(vec!["#.#."], to_strings(&xor));
+
+        assert!(a.or_assign(&BitGrid::new(2, 4)).is_err());
+    }
+
+    #[test]
+    fn test_shifted() {
+        let grid = BitGrid::from(
+            &Grid::new(vec![
+                vec![true, false, false], // force multi-line format
+                vec![false, true, true],
+            ])
+            .expect("succeeds"),
+        );
+        assert_eq!(vec!["...", "#.."], to_strings(&grid.shifted(1, 0)));
+        assert_eq!(vec![".#.", "..#"], to_strings(&grid.shifted(0, 1)));
+        assert_eq!(vec!["...", "##."], to_strings(&grid.shifted(0, -1)));
+        assert_eq!(vec![".##", "..."], to_strings(&grid.shifted(-1, 0)));
+        assert_eq!(2, grid.shifted(0, 1).count_ones());
+    }
+
+    #[test]
+    fn test_shifted_across_words() {
+        let mut grid = BitGrid::new(1, 130);
+        grid.set(&Position::new(0, 63), true).expect("succeeds");
+        grid.set(&Position::new(0, 129), true).expect("succeeds");
+
+        let right = grid.shifted(0, 1);
+        assert_eq!(Some(true), right.get(&Position::new(0, 64)));
+        assert_eq!(1, right.count_ones());
+
+        let left = grid.shifted(0, -65);
+        assert_eq!(Some(true), left.get(&Position::new(0, 64)));
+        assert_eq!(1, left.count_ones());
+    }
+}
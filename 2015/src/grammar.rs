@@ -0,0 +1,240 @@
+use crate::graph::Interner;
+use anyhow::{Result, anyhow};
+use std::cmp::Reverse;
+use std::collections::hash_map::Entry;
+use std::collections::{BinaryHeap, HashMap};
+
+/// A symbol of the grammar, or a stand-in for the first few symbols on the right of some rule
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum Node {
+    Symbol(usize),
+    Prefix(usize),
+}
+
+/// Cheapest known derivation of part of the input from each node
+type Cell = HashMap<Node, u64>;
+
+/// Context-free grammar, where each rule rewrites one symbol as a sequence of symbols. Any symbol
+/// may appear in a derived string, so there's no split into terminals and non-terminals.
+///
+/// Rules are stored binarized, so `A => B C D` becomes `B C => [B C]` then `[B C] D => A`, with
+/// only the last step counting as a rule application.
+#[derive(Debug, Clone, Default)]
+pub struct Grammar {
+    symbols: Interner,
+    /// Per node, what a rule with it alone on the right rewrites from
+    unary: HashMap<Node, Vec<Node>>,
+    /// Per pair of adjacent nodes, what they combine into and how many rule applications it takes
+    binary: HashMap<(Node, Node), Vec<(Node, u64)>>,
+    /// Stand-ins for rule prefixes, shared between rules starting the same way
+    prefixes: HashMap<Vec<Node>, Node>,
+}
+
+impl Grammar {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the rule `lhs => rhs`
+    /// # Errors
+    /// If `rhs` is empty
+    pub fn add_rule(&mut self, lhs: &str, rhs: &[&str]) -> Result<()> {
+        let Some((&first, rest)) = rhs.split_first() else {
+            return Err(anyhow!("rule for {lhs} has nothing on the right"));
+        };
+        let lhs = Node::Symbol(self.symbols.intern(lhs));
+        let mut left = Node::Symbol(self.symbols.intern(first));
+        let Some((&last, middle)) = rest.split_last() else {
+            self.unary.entry(left).or_default().push(lhs);
+            return Ok(());
+        };
+
+        let mut prefix = vec![left];
+        for &symbol in middle {
+            let right = Node::Symbol(self.symbols.intern(symbol));
+            prefix.push(right);
+            let next_id = self.prefixes.len();
+            left = match self.prefixes.entry(prefix.clone()) {
+                Entry::Occupied(e) => *e.get(),
+                Entry::Vacant(e) => {
+                    let node = *e.insert(Node::Prefix(next_id));
+                    self.binary
+                        .entry((left, right))
+                        .or_default()
+                        .push((node, 0));
+                    node
+                }
+            };
+        }
+        let last = Node::Symbol(self.symbols.intern(last));
+        self.binary.entry((left, last)).or_default().push((lhs, 1));
+        Ok(())
+    }
+
+    /// Fewest rule applications turning `start` into exactly `symbols`, by CYK-style chart
+    /// parsing: the cheapest derivation of every span of `symbols` from every node is built up
+    /// from those of shorter spans.
+    /// # Errors
+    /// If `symbols` is empty, uses a symbol no rule mentions, or can't be derived from `start`
+    pub fn min_derivation_steps(&self, start: &str, symbols: &[&str]) -> Result<u64> {
+        let target = self
+            .symbols
+            .id(start)
+            .map(Node::Symbol)
+            .ok_or_else(|| anyhow!("{start} doesn't appear in any rule"))?;
+        let leaves = symbols
+            .iter()
+            .map(|s| {
+                self.symbols
+                    .id(s)
+                    .map(Node::Symbol)
+                    .ok_or_else(|| anyhow!("{s} doesn't appear in any rule"))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        if leaves.is_empty() {
+            return Err(anyhow!("nothing to derive"));
+        }
+
+        // chart[len - 1][i] covers symbols[i..i + len]
+        let n = leaves.len();
+        let mut chart: Vec<Vec<Cell>> = Vec::with_capacity(n);
+        chart.push(
+            leaves
+                .iter()
+                .map(|&leaf| self.close(HashMap::from([(leaf, 0)])))
+                .collect(),
+        );
+        for len in 2..=n {
+            let row = (0..=n - len)
+                .map(|i| {
+                    let mut cell = Cell::new();
+                    for left_len in 1..len {
+                        let (Some(left), Some(right)) = (
+                            cell_at(&chart, i, left_len),
+                            cell_at(&chart, i + left_len, len - left_len),
+                        ) else {
+                            continue;
+                        };
+                        self.combine(left, right, &mut cell);
+                    }
+                    self.close(cell)
+                })
+                .collect();
+            chart.push(row);
+        }
+
+        cell_at(&chart, 0, n)
+            .and_then(|cell| cell.get(&target))
+            .copied()
+            .ok_or_else(|| anyhow!("{} can't be derived from {start}", symbols.concat()))
+    }
+
+    /// Add to `cell` everything a binary rule makes from a node of `left` followed by one of
+    /// `right`
+    fn combine(&self, left: &Cell, right: &Cell, cell: &mut Cell) {
+        for (&l, &l_cost) in left {
+            for (&r, &r_cost) in right {
+                for &(node, cost) in self.binary.get(&(l, r)).into_iter().flatten() {
+                    relax(cell, node, l_cost + r_cost + cost);
+                }
+            }
+        }
+    }
+
+    /// Extend `cell` with every chain of single symbol rules, cheapest first
+    fn close(&self, mut cell: Cell) -> Cell {
+        let mut heap: BinaryHeap<_> = cell
+            .iter()
+            .map(|(&node, &cost)| Reverse((cost, node)))
+            .collect();
+        while let Some(Reverse((cost, node))) = heap.pop() {
+            if cell.get(&node).is_some_and(|&best| best < cost) {
+                continue;
+            }
+            for &parent in self.unary.get(&node).into_iter().flatten() {
+                if relax(&mut cell, parent, cost + 1) {
+                    heap.push(Reverse((cost + 1, parent)));
+                }
+            }
+        }
+        cell
+    }
+}
+
+fn cell_at(chart: &[Vec<Cell>], start: usize, len: usize) -> Option<&Cell> {
+    chart.get(len.checked_sub(1)?)?.get(start)
+}
+
+/// Record reaching `node` for `cost` if that's cheaper than before, returning whether it was
+fn relax(cell: &mut Cell, node: Node, cost: u64) -> bool {
+    match cell.entry(node) {
+        Entry::Occupied(mut e) if *e.get() > cost => {
+            e.insert(cost);
+            true
+        }
+        Entry::Occupied(_) => false,
+        Entry::Vacant(e) => {
+            e.insert(cost);
+            true
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grammar(rules: &[(&str, &str)]) -> Grammar {
+        let mut g = Grammar::new();
+        for &(lhs, rhs) in rules {
+            let rhs: Vec<_> = rhs.split_whitespace().collect();
+            g.add_rule(lhs, &rhs).expect("non-empty");
+        }
+        g
+    }
+
+    fn steps(g: &Grammar, symbols: &str) -> Result<u64> {
+        let symbols: Vec<_> = symbols.split_whitespace().collect();
+        g.min_derivation_steps("e", &symbols)
+    }
+
+    #[test]
+    fn test_min_derivation_steps() {
+        let g = grammar(&[
+            ("e", "H"),
+            ("e", "O"),
+            ("H", "H O"),
+            ("H", "O H"),
+            ("O", "H H"),
+        ]);
+        assert_eq!(3, steps(&g, "H O H").expect("derivable"));
+        assert_eq!(6, steps(&g, "H O H O H O").expect("derivable"));
+        assert_eq!(0, g.min_derivation_steps("H", &["H"]).expect("no steps"));
+    }
+
+    #[test]
+    fn test_cheapest_of_several() {
+        // Shared prefixes, a long rule, and a chain of single symbol rules that costs more
+        let g = grammar(&[
+            ("e", "A"),
+            ("A", "x x y"),
+            ("e", "B"),
+            ("B", "C"),
+            ("C", "x x y"),
+            ("e", "x x z y"),
+        ]);
+        assert_eq!(2, steps(&g, "x x y").expect("derivable"));
+        assert_eq!(1, steps(&g, "x x z y").expect("derivable"));
+    }
+
+    #[test]
+    fn test_underivable() {
+        let g = grammar(&[("e", "A B"), ("A", "x"), ("B", "y")]);
+        assert_eq!(3, steps(&g, "x y").expect("derivable"));
+        assert!(steps(&g, "y x").is_err());
+        assert!(steps(&g, "x q").is_err());
+        assert!(steps(&g, "").is_err());
+        assert!(Grammar::new().add_rule("e", &[]).is_err());
+    }
+}
@@ -0,0 +1,3 @@
+pub use adv_code_common::graph::*;
+
+pub mod hamiltonian;
@@ -0,0 +1,193 @@
+use crate::graph::Graph;
+use std::ops::Add;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Objective {
+    Minimize,
+    Maximize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Route {
+    /// Visit every node once, ending anywhere
+    Path,
+    /// Visit every node once and then return to the first
+    Cycle,
+}
+
+/// An optimal order to visit the nodes in, as found by [`held_karp`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tour<W> {
+    score: W,
+    order: Vec<usize>,
+}
+
+impl<W: Copy> Tour<W> {
+    /// Total weight of the edges followed, including the closing edge of a cycle
+    #[must_use]
+    pub const fn score(&self) -> W {
+        self.score
+    }
+
+    /// Node ids in visiting order. For a cycle the first node is not repeated at the end.
+    #[must_use]
+    pub fn order(&self) -> &[usize] {
+        &self.order
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Entry<W> {
+    score: W,
+    prev: Option<usize>,
+}
+
+/// Best route through every node of the graph, using the Held-Karp bitmask dynamic program.
+/// Edges are followed in their stored direction, so asymmetric weights are respected.
+///
+/// The route starts at `start` if given. A cycle without a fixed start begins at node 0, which
+/// loses nothing since any rotation of a cycle scores the same.
+///
+/// Returns `None` if there is no such route, the graph is empty, or `start` isn't in the graph.
+/// Takes O(2^n n^2) time and O(2^n n) memory for n nodes, so it is practical up to about 20.
+pub fn held_karp<W>(
+    graph: &Graph<W>,
+    objective: Objective,
+    route: Route,
+    start: Option<usize>,
+) -> Option<Tour<W>>
+where
+    W: Copy + Ord + Default + Add<Output = W>,
+{
+    let n = graph.num_nodes();
+    if n == 0 || start.is_some_and(|s| s >= n) {
+        return None;
+    }
+    let num_masks = 1_usize.checked_shl(u32::try_from(n).ok()?)?;
+    let full = num_masks - 1;
+    let better = |a: W, b: W| match objective {
+        Objective::Minimize => a < b,
+        Objective::Maximize => a > b,
+    };
+
+    // best[mask * n + last]: best route visiting exactly the nodes in `mask`, ending at `last`
+    let mut best: Vec<Option<Entry<W>>> = vec![None; num_masks * n];
+    let starts = match (start, route) {
+        (Some(s), _) => s..s + 1,
+        (None, Route::Cycle) => 0..1,
+        (None, Route::Path) => 0..n,
+    };
+    for s in starts.clone() {
+        if let Some(slot) = best.get_mut((1 << s) * n + s) {
+            *slot = Some(Entry {
+                score: W::default(),
+                prev: None,
+            });
+        }
+    }
+
+    for mask in 1..num_masks {
+        for last in 0..n {
+            let Some(Some(entry)) = best.get(mask * n + last).copied() else {
+                continue;
+            };
+            for (next, &w) in graph.neighbors(last) {
+                if mask & (1 << next) != 0 {
+                    continue;
+                }
+                let score = entry.score + w;
+                let Some(slot) = best.get_mut((mask | (1 << next)) * n + next) else {
+                    continue;
+                };
+                if slot.is_none_or(|existing| better(score, existing.score)) {
+                    *slot = Some(Entry {
+                        score,
+                        prev: Some(last),
+                    });
+                }
+            }
+        }
+    }
+
+    let closing = |last: usize| -> Option<W> {
+        match route {
+            Route::Path => Some(W::default()),
+            Route::Cycle => graph.weight(last, starts.start).copied(),
+        }
+    };
+    let (score, last) = (0..n)
+        .filter_map(|last| {
+            let entry = best.get(full * n + last).copied().flatten()?;
+            Some((entry.score + closing(last)?, last))
+        })
+        .reduce(|a, b| if better(b.0, a.0) { b } else { a })?;
+
+    let mut order = vec![last];
+    let mut mask = full;
+    let mut curr = last;
+    while let Some(prev) = best.get(mask * n + curr).copied().flatten()?.prev {
+        mask &= !(1 << curr);
+        order.push(prev);
+        curr = prev;
+    }
+    order.reverse();
+    Some(Tour { score, order })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{Direction, Storage};
+
+    fn cities() -> Graph<u32> {
+        let mut g = Graph::new(Direction::Undirected, Storage::Dense);
+        g.add_edge("London", "Dublin", 464);
+        g.add_edge("London", "Belfast", 518);
+        g.add_edge("Dublin", "Belfast", 141);
+        g
+    }
+
+    #[test]
+    fn test_path() {
+        let g = cities();
+        let shortest = held_karp(&g, Objective::Minimize, Route::Path, None).expect("route");
+        assert_eq!(605, shortest.score());
+        assert_eq!(3, shortest.order().len());
+        let longest = held_karp(&g, Objective::Maximize, Route::Path, None).expect("route");
+        assert_eq!(982, longest.score());
+
+        let dublin = g.id("Dublin").expect("exists");
+        let from_dublin =
+            held_karp(&g, Objective::Minimize, Route::Path, Some(dublin)).expect("route");
+        assert_eq!(659, from_dublin.score());
+        assert_eq!(Some(&dublin), from_dublin.order().first());
+    }
+
+    #[test]
+    fn test_cycle_asymmetric() {
+        // Going round a -> b -> c -> d is cheap, the other way is expensive
+        let mut g = Graph::new(Direction::Directed, Storage::AdjacencyList);
+        for (from, to) in [("a", "b"), ("b", "c"), ("c", "d"), ("d", "a")] {
+            g.add_edge(from, to, 1);
+            g.add_edge(to, from, 10);
+        }
+        let cheapest = held_karp(&g, Objective::Minimize, Route::Cycle, None).expect("route");
+        assert_eq!(4, cheapest.score());
+        assert_eq!(vec![0, 1, 2, 3], cheapest.order());
+        let dearest = held_karp(&g, Objective::Maximize, Route::Cycle, None).expect("route");
+        assert_eq!(40, dearest.score());
+        assert_eq!(vec![0, 3, 2, 1], dearest.order());
+    }
+
+    #[test]
+    fn test_no_route() {
+        let mut g = Graph::new(Direction::Directed, Storage::AdjacencyList);
+        g.add_edge("a", "b", 1);
+        g.add_edge("a", "c", 1);
+        assert!(held_karp(&g, Objective::Minimize, Route::Path, None).is_none());
+        assert!(held_karp(&g, Objective::Minimize, Route::Path, Some(3)).is_none());
+
+        let empty: Graph<u32> = Graph::new(Direction::Directed, Storage::Dense);
+        assert!(held_karp(&empty, Objective::Minimize, Route::Path, None).is_none());
+    }
+}
@@ -12,6 +12,16 @@ impl Position {
         Self { row, col }
     }
 
+    #[must_use]
+    pub const fn row(&self) -> usize {
+        self.row
+    }
+
+    #[must_use]
+    pub const fn col(&self) -> usize {
+        self.col
+    }
+
     pub fn adjacent(&self) -> impl Iterator<Item = Self> + '_ {
         (-1..=1).flat_map(move |row_offset| {
             (-1..=1).filter_map(move |col_offset| {
@@ -1,4 +1,11 @@
+pub mod bit_grid;
+pub mod grammar;
+pub mod graph;
 pub mod grids;
+pub mod memo;
+pub mod partition;
+pub mod recipe;
+pub mod rect_grid;
 
 pub fn start_day(day: &str) {
     println!("Advent of Code 2025 - Day {day:0>2}");
@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// Cache for a recursive function's results.
+///
+/// Recursion over a slice is best keyed by the index where the remaining suffix starts, along
+/// with whatever else the function depends on, e.g. `(usize, u16)` for "ways to make this total
+/// from the items after `idx`". Unlike keying by the suffix itself, that doesn't tie the cache to
+/// the slice's lifetime, and hashes in constant time.
+#[derive(Debug, Clone)]
+pub struct Memo<K, V> {
+    cache: HashMap<K, V>,
+    stats: MemoStats,
+}
+
+/// How often a [`Memo`] had a result ready
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoStats {
+    pub hits: usize,
+    pub misses: usize,
+}
+
+impl<K: Eq + Hash, V: Clone> Memo<K, V> {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// Sized up front for `capacity` results, e.g. the number of suffixes times the number of
+    /// states, to save rehashing as it fills
+    #[must_use]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            cache: HashMap::with_capacity(capacity),
+            stats: MemoStats::default(),
+        }
+    }
+
+    /// The cached result for `key`, or else the result of `compute`, which is cached for next
+    /// time. `compute` is given the memo back so it can make its recursive calls through it.
+    pub fn get_or_compute<F>(&mut self, key: K, compute: F) -> V
+    where
+        F: FnOnce(&mut Self) -> V,
+    {
+        if let Some(cached) = self.cache.get(&key) {
+            self.stats.hits += 1;
+            return cached.clone();
+        }
+        self.stats.misses += 1;
+        let value = compute(self);
+        self.cache.insert(key, value.clone());
+        value
+    }
+
+    #[must_use]
+    pub const fn stats(&self) -> MemoStats {
+        self.stats
+    }
+
+    /// Number of results cached
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.cache.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.cache.is_empty()
+    }
+}
+
+impl<K: Eq + Hash, V: Clone> Default for Memo<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fib(n: u64, memo: &mut Memo<u64, u64>) -> u64 {
+        if n < 2 {
+            return n;
+        }
+        memo.get_or_compute(n, |memo| fib(n - 1, memo) + fib(n - 2, memo))
+    }
+
+    /// Ways to pick items from `items[idx..]` adding up to `target`
+    fn subset_sums(
+        items: &[u32],
+        idx: usize,
+        target: u32,
+        memo: &mut Memo<(usize, u32), usize>,
+    ) -> usize {
+        if target == 0 {
+            return 1;
+        }
+        let Some(&item) = items.get(idx) else {
+            return 0;
+        };
+        memo.get_or_compute((idx, target), |memo| {
+            let with = if item <= target {
+                subset_sums(items, idx + 1, target - item, memo)
+            } else {
+                0
+            };
+            with + subset_sums(items, idx + 1, target, memo)
+        })
+    }
+
+    #[test]
+    fn test_fib() {
+        let mut memo = Memo::new();
+        assert_eq!(12_586_269_025, fib(50, &mut memo));
+        // Each n from 2 to 50 is computed once, and all but the last two asked for again
+        assert_eq!(
+            MemoStats {
+                hits: 47,
+                misses: 49
+            },
+            memo.stats()
+        );
+        assert_eq!(49, memo.len());
+    }
+
+    #[test]
+    fn test_suffix_keys() {
+        let items = [20, 15, 10, 5, 5];
+        let mut memo = Memo::with_capacity(items.len() * 26);
+        assert_eq!(4, subset_sums(&items, 0, 25, &mut memo));
+        assert!(memo.stats().hits > 0);
+
+        let misses = memo.stats().misses;
+        assert_eq!(4, subset_sums(&items, 0, 25, &mut memo));
+        assert_eq!(misses, memo.stats().misses);
+    }
+}
@@ -0,0 +1,349 @@
+use anyhow::{Result, anyhow};
+
+/// Largest group weight handled. The search keeps a table of which sums up to the group weight are
+/// reachable, one `bool` per sum per item, so this caps it at about a megabyte per item.
+pub const MAX_TARGET: u64 = 1 << 20;
+
+/// Split the items `0..weights.len()` into `k` groups of equal total weight.
+///
+/// Of all such splits, the one chosen has the smallest possible first group, with ties between
+/// first groups of the same size going to the lowest `rank`. First groups are tried in that
+/// order, each kept only if the rest can still be split evenly. Groups are lists of indices in
+/// increasing order. Returns `None` if there's no way to split the items.
+///
+/// Every first group of a given size is generated and ranked before any is tried, so memory grows
+/// with the number of subsets of that size adding up to the group weight.
+/// # Errors
+/// If `k` is zero, or the group weight is over [`MAX_TARGET`]
+pub fn balanced_partition<K, F>(
+    weights: &[u64],
+    k: usize,
+    rank: F,
+) -> Result<Option<Vec<Vec<usize>>>>
+where
+    K: Ord,
+    F: Fn(&[usize]) -> K,
+{
+    if k == 0 {
+        return Err(anyhow!("can't split into zero groups"));
+    }
+    let total: u64 = weights.iter().sum();
+    let k_weight = u64::try_from(k)?;
+    if !total.is_multiple_of(k_weight) {
+        return Ok(None);
+    }
+    let target = total / k_weight;
+    check_target(target)?;
+    if k == 1 {
+        return Ok(Some(vec![(0..weights.len()).collect()]));
+    }
+
+    let items = heaviest_first(weights, (0..weights.len()).collect());
+    for size in 1..=items.len() {
+        let mut candidates = Vec::new();
+        subsets_of_size(&items, size, target, &mut Vec::new(), &mut candidates);
+        for group in &mut candidates {
+            group.sort_unstable();
+        }
+        candidates.sort_by_cached_key(|group| rank(group));
+
+        for first in candidates {
+            let mut in_first = vec![false; weights.len()];
+            for &i in &first {
+                if let Some(taken) = in_first.get_mut(i) {
+                    *taken = true;
+                }
+            }
+            let rest: Vec<usize> = (0..weights.len())
+                .filter(|&i| !in_first.get(i).copied().unwrap_or(false))
+                .collect();
+            if let Some(others) = split_items(weights, &rest, k - 1, target) {
+                return Ok(Some([vec![first], others].concat()));
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Split the given items into `groups` groups each weighing `target`, if that's possible.
+///
+/// Each group in turn is made to include the heaviest item left, so no split is tried twice in
+/// a different order. A table of which sums the remaining items can still reach steers the
+/// search away from dead ends.
+/// # Errors
+/// If `target` is over [`MAX_TARGET`]
+pub fn split_equal(
+    weights: &[u64],
+    items: &[usize],
+    groups: usize,
+    target: u64,
+) -> Result<Option<Vec<Vec<usize>>>> {
+    check_target(target)?;
+    Ok(split_items(weights, items, groups, target))
+}
+
+fn check_target(target: u64) -> Result<()> {
+    if target > MAX_TARGET {
+        return Err(anyhow!("group weight {target} is over {MAX_TARGET}"));
+    }
+    Ok(())
+}
+
+/// [`split_equal`] for a target already known to be small enough
+fn split_items(
+    weights: &[u64],
+    items: &[usize],
+    groups: usize,
+    target: u64,
+) -> Option<Vec<Vec<usize>>> {
+    let items = heaviest_first(weights, items.to_vec());
+    if groups == 0 {
+        // Nothing left to put items in, which is only fine if they weigh nothing
+        return items.iter().all(|&(_, w)| w == 0).then(Vec::new);
+    }
+    let (&(first, first_weight), rest) = items.split_first()?;
+    let need = target.checked_sub(first_weight)?;
+    if groups == 1 {
+        let sum: u64 = items.iter().map(|&(_, w)| w).sum();
+        let mut group: Vec<usize> = items.iter().map(|&(i, _)| i).collect();
+        group.sort_unstable();
+        return (sum == target).then(|| vec![group]);
+    }
+
+    let mut chosen = vec![false; weights.len()];
+    if let Some(taken) = chosen.get_mut(first) {
+        *taken = true;
+    }
+    let mut search = GroupSearch {
+        weights,
+        rest,
+        reachable: reachable_sums(rest, need),
+        groups,
+        target,
+        chosen,
+    };
+    search.extend(0, need)
+}
+
+/// Backtracking state for [`split_equal`], filling out the group of the heaviest item
+struct GroupSearch<'a> {
+    weights: &'a [u64],
+    /// Every item but the heaviest, heaviest first
+    rest: &'a [(usize, u64)],
+    /// `reachable[i][s]` is whether some of `rest[i..]` add up to `s`
+    reachable: Vec<Vec<bool>>,
+    groups: usize,
+    target: u64,
+    /// Whether each item is in the group so far, by index into `weights`
+    chosen: Vec<bool>,
+}
+
+impl GroupSearch<'_> {
+    /// Add items from `rest[from..]` weighing `need` in all, then split what's left
+    fn extend(&mut self, from: usize, need: u64) -> Option<Vec<Vec<usize>>> {
+        if need == 0 {
+            let others: Vec<usize> = self
+                .rest
+                .iter()
+                .map(|&(i, _)| i)
+                .filter(|&i| !self.is_chosen(i))
+                .collect();
+            let mut split = split_items(self.weights, &others, self.groups - 1, self.target)?;
+            let group = (0..self.chosen.len())
+                .filter(|&i| self.is_chosen(i))
+                .collect();
+            split.insert(0, group);
+            return Some(split);
+        }
+        if !self.can_reach(from, need) {
+            return None;
+        }
+
+        let &(item, weight) = self.rest.get(from)?;
+        if let Some(left) = need.checked_sub(weight) {
+            self.choose(item, true);
+            let found = self.extend(from + 1, left);
+            self.choose(item, false);
+            if found.is_some() {
+                return found;
+            }
+        }
+        self.extend(from + 1, need)
+    }
+
+    fn is_chosen(&self, item: usize) -> bool {
+        self.chosen.get(item).copied().unwrap_or(false)
+    }
+
+    fn choose(&mut self, item: usize, chosen: bool) {
+        if let Some(slot) = self.chosen.get_mut(item) {
+            *slot = chosen;
+        }
+    }
+
+    fn can_reach(&self, from: usize, sum: u64) -> bool {
+        let Ok(sum) = usize::try_from(sum) else {
+            return false;
+        };
+        self.reachable
+            .get(from)
+            .and_then(|sums| sums.get(sum))
+            .copied()
+            .unwrap_or(false)
+    }
+}
+
+/// Subset sum table for `items`, covering the sums up to `max`
+fn reachable_sums(items: &[(usize, u64)], max: u64) -> Vec<Vec<bool>> {
+    let max = usize::try_from(max).unwrap_or(usize::MAX);
+    let mut table = vec![vec![false; max + 1]; items.len() + 1];
+    for row in &mut table {
+        if let Some(zero) = row.first_mut() {
+            *zero = true;
+        }
+    }
+    for (i, &(_, weight)) in items.iter().enumerate().rev() {
+        let weight = usize::try_from(weight).unwrap_or(usize::MAX);
+        let Some(next) = table.get(i + 1).cloned() else {
+            continue;
+        };
+        if let Some(row) = table.get_mut(i) {
+            for (sum, reached) in row.iter_mut().enumerate() {
+                let with = sum
+                    .checked_sub(weight)
+                    .and_then(|s| next.get(s))
+                    .copied()
+                    .unwrap_or(false);
+                *reached = with || next.get(sum).copied().unwrap_or(false);
+            }
+        }
+    }
+    table
+}
+
+/// The given items paired with their weights, heaviest first
+fn heaviest_first(weights: &[u64], items: Vec<usize>) -> Vec<(usize, u64)> {
+    let mut items: Vec<_> = items
+        .into_iter()
+        .filter_map(|i| Some((i, *weights.get(i)?)))
+        .collect();
+    items.sort_by_key(|&(i, w)| (std::cmp::Reverse(w), i));
+    items
+}
+
+/// Every way of choosing `size` of `items` adding up to `target`, appended to `found`
+fn subsets_of_size(
+    items: &[(usize, u64)],
+    size: usize,
+    target: u64,
+    chosen: &mut Vec<usize>,
+    found: &mut Vec<Vec<usize>>,
+) {
+    if size == 0 {
+        if target == 0 {
+            found.push(chosen.clone());
+        }
+        return;
+    }
+    // Items are heaviest first, so if the next few can't reach the target nothing later can
+    let most: u64 = items.iter().take(size).map(|&(_, w)| w).sum();
+    if most < target {
+        return;
+    }
+    for (pos, &(item, weight)) in items.iter().enumerate() {
+        let Some(left) = target.checked_sub(weight) else {
+            continue;
+        };
+        chosen.push(item);
+        subsets_of_size(
+            items.get(pos + 1..).unwrap_or_default(),
+            size - 1,
+            left,
+            chosen,
+            found,
+        );
+        chosen.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn total(weights: &[u64], group: &[usize]) -> u64 {
+        group.iter().filter_map(|&i| weights.get(i)).sum()
+    }
+
+    fn product(weights: &[u64], group: &[usize]) -> u64 {
+        group.iter().filter_map(|&i| weights.get(i)).product()
+    }
+
+    #[test]
+    fn test_split_equal() {
+        let weights = [5, 4, 3, 3, 3, 2];
+        let all: Vec<usize> = (0..weights.len()).collect();
+        let groups = split_equal(&weights, &all, 2, 10)
+            .expect("small target")
+            .expect("splits");
+        assert_eq!(2, groups.len());
+        assert!(groups.iter().all(|g| total(&weights, g) == 10));
+
+        // The totals work out, but nothing goes with the 4
+        assert_eq!(
+            None,
+            split_equal(&weights, &all, 4, 5).expect("small target")
+        );
+        let groups = split_equal(&weights, &[1, 2, 3, 4, 5], 3, 5).expect("small target");
+        assert_eq!(None, groups);
+        let groups = split_equal(&[5, 4, 3, 3], &[0, 1, 2, 3], 3, 5).expect("small target");
+        assert_eq!(None, groups);
+
+        assert!(split_equal(&weights, &all, 2, MAX_TARGET + 1).is_err());
+    }
+
+    #[test]
+    fn test_no_groups_left() {
+        assert_eq!(
+            Some(vec![]),
+            split_equal(&[0], &[0], 0, 0).expect("small target")
+        );
+        assert_eq!(
+            None,
+            split_equal(&[0, 1], &[0, 1], 0, 0).expect("small target")
+        );
+        let groups = balanced_partition(&[0, 0], 1, |g: &[usize]| g.len()).expect("k > 0");
+        assert_eq!(Some(vec![vec![0, 1]]), groups);
+    }
+
+    #[test]
+    fn test_balanced_partition() {
+        let weights = [1, 2, 3, 4, 5, 7, 8, 9, 10, 11];
+        let groups = balanced_partition(&weights, 3, |g: &[usize]| product(&weights, g))
+            .expect("k is positive")
+            .expect("splits");
+        assert_eq!(Some(&vec![7, 9]), groups.first());
+        assert_eq!(3, groups.len());
+        assert!(groups.iter().all(|g| total(&weights, g) == 20));
+        assert_eq!(10, groups.iter().map(Vec::len).sum::<usize>());
+
+        assert_eq!(
+            None,
+            balanced_partition(&[4, 3, 3, 1], 3, |g: &[usize]| g.len()).expect("k > 0")
+        );
+        assert!(balanced_partition(&weights, 0, |g: &[usize]| g.len()).is_err());
+        let heavy = [MAX_TARGET + 1, MAX_TARGET + 1];
+        assert!(balanced_partition(&heavy, 2, |g: &[usize]| g.len()).is_err());
+    }
+
+    #[test]
+    fn test_first_group_must_leave_a_split() {
+        // 8 + 15 + 19 has the lowest product of the groups of three weighing 42, but then
+        // 10, 11, 12, 16, 17, 18 can't make two more
+        let weights = [8, 10, 11, 12, 15, 16, 17, 18, 19];
+        let groups = balanced_partition(&weights, 3, |g: &[usize]| product(&weights, g))
+            .expect("k is positive")
+            .expect("splits");
+        assert_eq!(Some(&vec![0, 5, 7]), groups.first());
+        assert!(groups.iter().all(|g| total(&weights, g) == 42));
+    }
+}
@@ -0,0 +1,650 @@
+use anyhow::{Result, anyhow};
+
+/// How a property total compares to a constraint's value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Relation {
+    Equal,
+    AtMost,
+    AtLeast,
+}
+
+/// A limit on the total of one property over the whole recipe
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Constraint {
+    pub property: usize,
+    pub relation: Relation,
+    pub value: i64,
+}
+
+impl Constraint {
+    #[must_use]
+    pub const fn equal(property: usize, value: i64) -> Self {
+        Self {
+            property,
+            relation: Relation::Equal,
+            value,
+        }
+    }
+
+    #[must_use]
+    pub const fn at_most(property: usize, value: i64) -> Self {
+        Self {
+            property,
+            relation: Relation::AtMost,
+            value,
+        }
+    }
+
+    #[must_use]
+    pub const fn at_least(property: usize, value: i64) -> Self {
+        Self {
+            property,
+            relation: Relation::AtLeast,
+            value,
+        }
+    }
+
+    /// Whether some total between `low` and `high` would satisfy the constraint
+    const fn allows_range(&self, low: i128, high: i128) -> bool {
+        let value = self.value as i128;
+        match self.relation {
+            Relation::Equal => low <= value && value <= high,
+            Relation::AtMost => low <= value,
+            Relation::AtLeast => high >= value,
+        }
+    }
+}
+
+/// Amount of each ingredient, and what that scores
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Recipe {
+    pub amounts: Vec<u32>,
+    pub score: u128,
+}
+
+/// Picks whole amounts of each ingredient adding up to a fixed total.
+///
+/// Every ingredient adds its properties once per unit, and a recipe scores the product of its
+/// totals of the scored properties, any negative total counting as zero.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecipeOptimizer {
+    /// Per ingredient, its properties
+    ingredients: Vec<Vec<i64>>,
+    total: u32,
+    scored: Vec<usize>,
+    constraints: Vec<Constraint>,
+}
+
+impl RecipeOptimizer {
+    /// # Errors
+    /// If there are no ingredients, they don't all have the same number of properties, or a
+    /// scored property doesn't exist
+    pub fn new(ingredients: Vec<Vec<i64>>, total: u32, scored: Vec<usize>) -> Result<Self> {
+        let properties = ingredients
+            .first()
+            .ok_or_else(|| anyhow!("no ingredients"))?
+            .len();
+        if ingredients.iter().any(|i| i.len() != properties) {
+            return Err(anyhow!("ingredients have differing numbers of properties"));
+        }
+        if let Some(p) = scored.iter().find(|&&p| p >= properties) {
+            return Err(anyhow!("no property {p} to score"));
+        }
+        Ok(Self {
+            ingredients,
+            total,
+            scored,
+            constraints: vec![],
+        })
+    }
+
+    /// Only allow recipes meeting `constraint`
+    /// # Errors
+    /// If the constrained property doesn't exist
+    pub fn add_constraint(&mut self, constraint: Constraint) -> Result<()> {
+        if constraint.property >= self.num_properties() {
+            return Err(anyhow!("no property {} to constrain", constraint.property));
+        }
+        self.constraints.push(constraint);
+        Ok(())
+    }
+
+    #[must_use]
+    pub fn num_properties(&self) -> usize {
+        self.ingredients.first().map_or(0, Vec::len)
+    }
+
+    /// Highest scoring recipe meeting every constraint, or `None` if no recipe does.
+    ///
+    /// Amounts are chosen one ingredient at a time, by branch and bound. Branches are cut if
+    /// they can't meet the constraints or beat the best recipe so far, first by what's left
+    /// going all on whichever remaining ingredient is best for each property, then by the best
+    /// the rest could do with fractional amounts.
+    /// # Errors
+    /// If a score doesn't fit in a `u128`
+    pub fn best(&self) -> Result<Option<Recipe>> {
+        let mut search = Search::new(self);
+        search.visit(0, self.total)?;
+        Ok(search.best)
+    }
+}
+
+/// Branch and bound state for [`RecipeOptimizer::best`]
+struct Search<'a> {
+    problem: &'a RecipeOptimizer,
+    /// `highest[i][p]` is the most of property `p` in a unit of any of ingredients `i..`
+    highest: Vec<Vec<i128>>,
+    /// `lowest[i][p]` is the least of property `p` in a unit of any of ingredients `i..`
+    lowest: Vec<Vec<i128>>,
+    amounts: Vec<u32>,
+    /// Property totals of the amounts chosen so far
+    totals: Vec<i128>,
+    best: Option<Recipe>,
+}
+
+impl<'a> Search<'a> {
+    fn new(problem: &'a RecipeOptimizer) -> Self {
+        let properties = problem.num_properties();
+        let mut highest = vec![vec![i128::MIN; properties]];
+        let mut lowest = vec![vec![i128::MAX; properties]];
+        for ingredient in problem.ingredients.iter().rev() {
+            let (Some(high), Some(low)) = (highest.last(), lowest.last()) else {
+                unreachable!("starts non-empty");
+            };
+            let high = high
+                .iter()
+                .zip(ingredient)
+                .map(|(&h, &v)| h.max(i128::from(v)))
+                .collect();
+            let low = low
+                .iter()
+                .zip(ingredient)
+                .map(|(&l, &v)| l.min(i128::from(v)))
+                .collect();
+            highest.push(high);
+            lowest.push(low);
+        }
+        highest.reverse();
+        lowest.reverse();
+
+        Self {
+            problem,
+            highest,
+            lowest,
+            amounts: Vec::with_capacity(problem.ingredients.len()),
+            totals: vec![0; properties],
+            best: None,
+        }
+    }
+
+    /// Try every amount of ingredient `idx`, with `left` units still to add
+    fn visit(&mut self, idx: usize, left: u32) -> Result<()> {
+        let Some(ingredient) = self.problem.ingredients.get(idx) else {
+            return Ok(());
+        };
+        if idx + 1 == self.problem.ingredients.len() {
+            self.add(ingredient, left);
+            let result = self.finish();
+            self.remove(ingredient, left);
+            return result;
+        }
+        if !self.can_satisfy(idx, left) || !self.can_improve(idx, left) {
+            return Ok(());
+        }
+        let relaxed = self.relax(idx, left);
+        if let (Some(best), Some(relaxed)) = (&self.best, &relaxed)
+            && !relaxed.could_beat(best.score)
+        {
+            return Ok(());
+        }
+
+        // Otherwise start near the fractional optimum, where good recipes are likely to be
+        let amounts = self.forced(idx, left).unwrap_or_else(|| {
+            let first = relaxed.map_or(left, |r| r.first_amount(left));
+            outward(first, left).collect()
+        });
+        for amount in amounts {
+            self.add(ingredient, amount);
+            let result = self.visit(idx + 1, left - amount);
+            self.remove(ingredient, amount);
+            result?;
+        }
+        Ok(())
+    }
+
+    /// Record the complete recipe if it's allowed and the best yet
+    fn finish(&mut self) -> Result<()> {
+        let allowed = self.problem.constraints.iter().all(|c| {
+            let total = self.total(c.property);
+            c.allows_range(total, total)
+        });
+        if !allowed {
+            return Ok(());
+        }
+        let score = self
+            .problem
+            .scored
+            .iter()
+            .try_fold(1u128, |score, &p| {
+                let total = u128::try_from(self.total(p).max(0)).ok()?;
+                score.checked_mul(total)
+            })
+            .ok_or_else(|| anyhow!("recipe {:?} scores too much", self.amounts))?;
+        if self.best.as_ref().is_none_or(|best| score > best.score) {
+            self.best = Some(Recipe {
+                amounts: self.amounts.clone(),
+                score,
+            });
+        }
+        Ok(())
+    }
+
+    /// With two ingredients left, an equality constraint on a property they differ in leaves at
+    /// most one choice for the first of them. `None` if there's no such constraint.
+    fn forced(&self, idx: usize, left: u32) -> Option<Vec<u32>> {
+        let [this, last] = self.problem.ingredients.get(idx..)? else {
+            return None;
+        };
+        self.problem.constraints.iter().find_map(|c| {
+            if c.relation != Relation::Equal {
+                return None;
+            }
+            let this = i128::from(*this.get(c.property)?);
+            let last = i128::from(*last.get(c.property)?);
+            if this == last {
+                return None;
+            }
+            // total + amount * this + (left - amount) * last == value
+            let need = i128::from(c.value) - self.total(c.property) - i128::from(left) * last;
+            let diff = this - last;
+            let amount = (need % diff == 0)
+                .then(|| u32::try_from(need / diff).ok())
+                .flatten()
+                .filter(|&a| a <= left);
+            Some(amount.into_iter().collect())
+        })
+    }
+
+    /// Whether spreading `left` units over ingredients `idx..` could meet every constraint
+    fn can_satisfy(&self, idx: usize, left: u32) -> bool {
+        self.problem.constraints.iter().all(|c| {
+            let (low, high) = self.range(idx, left, c.property);
+            c.allows_range(low, high)
+        })
+    }
+
+    /// Whether spreading `left` units over ingredients `idx..` could beat the best score so far
+    fn can_improve(&self, idx: usize, left: u32) -> bool {
+        let Some(best) = &self.best else {
+            return true;
+        };
+        let bound = self.problem.scored.iter().fold(1u128, |bound, &p| {
+            let (_, high) = self.range(idx, left, p);
+            bound.saturating_mul(u128::try_from(high.max(0)).unwrap_or(u128::MAX))
+        });
+        bound > best.score
+    }
+
+    /// Frank-Wolfe on the log of the score, with amounts allowed to be fractional. The log of the
+    /// score is concave wherever every scored total is positive, so at any such point its
+    /// linearization bounds it from above, and the best point along that is easy to find. One
+    /// constraint, an equality if there is one, is kept in the fractional version, the others
+    /// left to the exact checks. Gives up if no point with every scored total positive turns up.
+    fn relax(&self, idx: usize, left: u32) -> Option<Relaxation> {
+        let ingredients = self.problem.ingredients.get(idx..)?;
+        let scored = &self.problem.scored;
+        if scored.is_empty() || left == 0 {
+            return None;
+        }
+        // Per ingredient, by how much putting everything left into it meets the constraint
+        let slack = self.relaxed_constraint().map(|c| {
+            let sign = if c.relation == Relation::AtMost {
+                -1
+            } else {
+                1
+            };
+            let slack: Vec<i128> = ingredients
+                .iter()
+                .map(|ingredient| {
+                    let unit = i128::from(ingredient.get(c.property).copied().unwrap_or_default());
+                    sign * (self.total(c.property) + i128::from(left) * unit - i128::from(c.value))
+                })
+                .collect();
+            (slack, c.relation == Relation::Equal)
+        });
+        let left = f64::from(left);
+        let base: Vec<f64> = scored.iter().map(|&p| lossy(self.total(p))).collect();
+        let units: Vec<Vec<f64>> = ingredients
+            .iter()
+            .map(|ingredient| {
+                scored
+                    .iter()
+                    .map(|&p| lossy(ingredient.get(p).copied().unwrap_or_default().into()))
+                    .collect()
+            })
+            .collect();
+        let scored_totals = |amounts: &[f64]| -> Vec<f64> {
+            base.iter()
+                .enumerate()
+                .map(|(k, &b)| {
+                    b + amounts
+                        .iter()
+                        .zip(&units)
+                        .map(|(&x, unit)| x * unit.get(k).copied().unwrap_or_default())
+                        .sum::<f64>()
+                })
+                .collect()
+        };
+
+        let mut amounts =
+            vec![left / lossy(i128::try_from(ingredients.len()).ok()?); ingredients.len()];
+        let mut relaxed: Option<Relaxation> = None;
+        for step in 0..RELAXATION_STEPS {
+            let totals = scored_totals(&amounts);
+            let towards = if totals.iter().all(|&t| t > 0.0) {
+                let gradient: Vec<f64> = units
+                    .iter()
+                    .map(|unit| unit.iter().zip(&totals).map(|(u, t)| u / t).sum())
+                    .collect();
+                let (best, towards) = best_point(
+                    &gradient,
+                    slack.as_ref().map(|(s, equal)| (s.as_slice(), *equal)),
+                    left,
+                )?;
+                let log_score: f64 = totals.iter().map(|t| t.ln()).sum();
+                let along: f64 = gradient.iter().zip(&amounts).map(|(g, x)| g * x).sum();
+                let log_bound = log_score + best - along;
+                if relaxed.as_ref().is_none_or(|r| log_bound < r.log_bound) {
+                    relaxed = Some(Relaxation {
+                        log_bound,
+                        first: amounts.first().copied().unwrap_or_default(),
+                    });
+                }
+                towards
+            } else {
+                // Head for whichever ingredient does most for the worst total
+                let (worst, _) = min_by_value(&totals)?;
+                let most: Vec<f64> = units
+                    .iter()
+                    .map(|unit| unit.get(worst).copied().unwrap_or_default())
+                    .collect();
+                vec![(max_by_value(&most)?.0, left)]
+            };
+
+            let rate = 2.0 / (f64::from(u32::try_from(step).ok()?) + 2.0);
+            for x in &mut amounts {
+                *x *= 1.0 - rate;
+            }
+            for (j, amount) in towards {
+                if let Some(x) = amounts.get_mut(j) {
+                    *x += rate * amount;
+                }
+            }
+        }
+        relaxed
+    }
+
+    /// The constraint kept when amounts are fractional
+    fn relaxed_constraint(&self) -> Option<&Constraint> {
+        let constraints = &self.problem.constraints;
+        constraints
+            .iter()
+            .find(|c| c.relation == Relation::Equal)
+            .or_else(|| constraints.first())
+    }
+
+    /// Lowest and highest possible totals of property `p` after spreading `left` units over
+    /// ingredients `idx..`
+    fn range(&self, idx: usize, left: u32, p: usize) -> (i128, i128) {
+        let extreme = |table: &[Vec<i128>]| {
+            table
+                .get(idx)
+                .and_then(|row| row.get(p))
+                .copied()
+                .expect("property and ingredient in range by construction")
+        };
+        let left = i128::from(left);
+        let total = self.total(p);
+        (
+            total + left * extreme(&self.lowest),
+            total + left * extreme(&self.highest),
+        )
+    }
+
+    fn total(&self, p: usize) -> i128 {
+        self.totals.get(p).copied().unwrap_or_default()
+    }
+
+    fn add(&mut self, ingredient: &[i64], amount: u32) {
+        for (total, &v) in self.totals.iter_mut().zip(ingredient) {
+            *total += i128::from(amount) * i128::from(v);
+        }
+        self.amounts.push(amount);
+    }
+
+    fn remove(&mut self, ingredient: &[i64], amount: u32) {
+        for (total, &v) in self.totals.iter_mut().zip(ingredient) {
+            *total -= i128::from(amount) * i128::from(v);
+        }
+        self.amounts.pop();
+    }
+}
+
+/// Bound and hint from the fractional version of the rest of the search
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Relaxation {
+    /// Natural log of an upper bound on the score
+    log_bound: f64,
+    /// Amount of the next ingredient at the best fractional point found
+    first: f64,
+}
+
+impl Relaxation {
+    fn could_beat(&self, score: u128) -> bool {
+        // Leave room for rounding errors
+        self.log_bound + RELAXATION_SLACK > lossy_u128(score).ln()
+    }
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn first_amount(&self, left: u32) -> u32 {
+        // Saturating casts, then clamp
+        (self.first.round() as u32).min(left)
+    }
+}
+
+/// Rounds of Frank-Wolfe per bound
+const RELAXATION_STEPS: usize = 30;
+const RELAXATION_SLACK: f64 = 1e-6;
+
+/// Highest point by `gradient` of `x >= 0, sum(x) = left`, and its value, among those meeting a
+/// constraint with the given `slack` at each vertex. Equalities need zero slack, others just not
+/// negative. Such a point is either a vertex or a mix of two vertices either side of the
+/// constraint. Points are lists of amounts per ingredient, leaving out zeros.
+fn best_point(
+    gradient: &[f64],
+    slack: Option<(&[i128], bool)>,
+    left: f64,
+) -> Option<(f64, Vec<(usize, f64)>)> {
+    let Some((slack, equal)) = slack else {
+        let (j, g) = max_by_value(gradient)?;
+        return Some((left * g, vec![(j, left)]));
+    };
+    let vertices: Vec<_> = gradient
+        .iter()
+        .copied()
+        .zip(slack.iter().copied())
+        .collect();
+    let mut best: Option<(f64, Vec<(usize, f64)>)> = None;
+    for (j, &(g, s)) in vertices.iter().enumerate() {
+        if s == 0 || (!equal && s > 0) {
+            let value = left * g;
+            if best.as_ref().is_none_or(|(v, _)| value > *v) {
+                best = Some((value, vec![(j, left)]));
+            }
+        }
+    }
+    for (j, &(g_under, s_under)) in vertices.iter().enumerate().filter(|(_, (_, s))| *s < 0) {
+        for (k, &(g_over, s_over)) in vertices.iter().enumerate().filter(|(_, (_, s))| *s > 0) {
+            let over = left * lossy(-s_under) / lossy(s_over - s_under);
+            let under = left - over;
+            let value = under.mul_add(g_under, over * g_over);
+            if best.as_ref().is_none_or(|(v, _)| value > *v) {
+                best = Some((value, vec![(j, under), (k, over)]));
+            }
+        }
+    }
+    best
+}
+
+/// `start`, then moving out either side of it, every number from 0 to `max`
+fn outward(start: u32, max: u32) -> impl Iterator<Item = u32> {
+    (0..=max).flat_map(move |d| {
+        let above = start.checked_add(d).filter(|&a| a <= max);
+        let below = start.checked_sub(d).filter(|_| d > 0);
+        above.into_iter().chain(below)
+    })
+}
+
+fn max_by_value(values: &[f64]) -> Option<(usize, f64)> {
+    values
+        .iter()
+        .copied()
+        .enumerate()
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+}
+
+fn min_by_value(values: &[f64]) -> Option<(usize, f64)> {
+    values
+        .iter()
+        .copied()
+        .enumerate()
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+}
+
+/// Only used for bounds, which leave room for rounding
+#[allow(clippy::cast_precision_loss)]
+const fn lossy(v: i128) -> f64 {
+    v as f64
+}
+
+#[allow(clippy::cast_precision_loss)]
+const fn lossy_u128(v: u128) -> f64 {
+    v as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cookies() -> RecipeOptimizer {
+        RecipeOptimizer::new(
+            vec![vec![-1, -2, 6, 3, 8], vec![2, 3, -2, -1, 3]],
+            100,
+            vec![0, 1, 2, 3],
+        )
+        .expect("valid ingredients")
+    }
+
+    /// Every way of splitting `total` units between `n` ingredients
+    fn compositions(n: usize, total: u32) -> Vec<Vec<u32>> {
+        if n == 1 {
+            return vec![vec![total]];
+        }
+        (0..=total)
+            .flat_map(|a| {
+                compositions(n - 1, total - a)
+                    .into_iter()
+                    .map(move |mut rest| {
+                        rest.insert(0, a);
+                        rest
+                    })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_best() {
+        let mut optimizer = cookies();
+        let best = optimizer.best().expect("fits").expect("some recipe");
+        assert_eq!(vec![44, 56], best.amounts);
+        assert_eq!(62_842_880, best.score);
+
+        optimizer
+            .add_constraint(Constraint::equal(4, 500))
+            .expect("property exists");
+        let best = optimizer.best().expect("fits").expect("some recipe");
+        assert_eq!(vec![40, 60], best.amounts);
+        assert_eq!(57_600_000, best.score);
+
+        optimizer
+            .add_constraint(Constraint::at_least(4, 501))
+            .expect("property exists");
+        assert_eq!(None, optimizer.best().expect("fits"));
+    }
+
+    #[test]
+    fn test_against_brute_force() {
+        // Four ingredients that each spoil some property, scored on the first four with the
+        // calories held to every limit from impossible to unconstrained
+        let ingredients = vec![
+            vec![4, -2, 0, 0, 5],
+            vec![0, 5, -1, 0, 8],
+            vec![-1, 0, 5, 0, 6],
+            vec![0, 0, -2, 2, 1],
+        ];
+        let totals = |amounts: &[u32]| -> Vec<i64> {
+            (0..5)
+                .map(|p| {
+                    amounts
+                        .iter()
+                        .zip(&ingredients)
+                        .map(|(&a, i)| i64::from(a) * i.get(p).expect("has property"))
+                        .sum()
+                })
+                .collect()
+        };
+        let score = |totals: &[i64]| -> u128 {
+            totals
+                .iter()
+                .take(4)
+                .map(|&t| u128::try_from(t.max(0)).expect("non-negative"))
+                .product()
+        };
+
+        for limit in (15..=165).step_by(10) {
+            let mut at_most =
+                RecipeOptimizer::new(ingredients.clone(), 20, vec![0, 1, 2, 3]).expect("valid");
+            at_most
+                .add_constraint(Constraint::at_most(4, limit))
+                .expect("property exists");
+            let mut exactly = at_most.clone();
+            exactly
+                .add_constraint(Constraint::equal(4, limit))
+                .expect("property exists");
+
+            let recipes: Vec<Vec<i64>> = compositions(4, 20).iter().map(|a| totals(a)).collect();
+            let best_at_most = recipes
+                .iter()
+                .filter(|t| t.get(4) <= Some(&limit))
+                .map(|t| score(t))
+                .max();
+            let best_exactly = recipes
+                .iter()
+                .filter(|t| t.get(4) == Some(&limit))
+                .map(|t| score(t))
+                .max();
+            let found = |o: &RecipeOptimizer| o.best().expect("fits").map(|r| r.score);
+            assert_eq!(best_at_most, found(&at_most), "at most {limit}");
+            assert_eq!(best_exactly, found(&exactly), "exactly {limit}");
+        }
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(RecipeOptimizer::new(vec![], 100, vec![]).is_err());
+        assert!(RecipeOptimizer::new(vec![vec![1, 2], vec![3]], 100, vec![0]).is_err());
+        assert!(RecipeOptimizer::new(vec![vec![1, 2]], 100, vec![2]).is_err());
+        let mut optimizer = cookies();
+        assert!(optimizer.add_constraint(Constraint::equal(5, 0)).is_err());
+    }
+}
@@ -0,0 +1,211 @@
+use anyhow::{Result, anyhow};
+
+/// Axis-aligned rectangle of grid cells. Both corners are inclusive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rectangle {
+    top: usize,
+    left: usize,
+    bottom: usize,
+    right: usize,
+}
+
+impl Rectangle {
+    /// Build the rectangle spanned by two opposite (row, col) corners, in either order
+    #[must_use]
+    pub fn new(corner: (usize, usize), opposite: (usize, usize)) -> Self {
+        Self {
+            top: corner.0.min(opposite.0),
+            left: corner.1.min(opposite.1),
+            bottom: corner.0.max(opposite.0),
+            right: corner.1.max(opposite.1),
+        }
+    }
+
+    #[must_use]
+    pub const fn area(&self) -> u64 {
+        ((self.bottom - self.top + 1) as u64) * ((self.right - self.left + 1) as u64)
+    }
+}
+
+/// Grid of values that is only ever modified a whole rectangle at a time.
+///
+/// Rows and columns are compressed down to the edges of a set of rectangles known up front, so
+/// each stored cell stands in for a block of real cells that always share a value. The cost of
+/// updates and queries depends on how many rectangles there are, not on their area or on the size
+/// of the grid.
+#[derive(Clone)]
+pub struct RectGrid<T> {
+    // Sorted, deduplicated cell boundaries. Compressed row `i` covers real rows
+    // `row_bounds[i]..row_bounds[i + 1]`, likewise for columns.
+    row_bounds: Vec<usize>,
+    col_bounds: Vec<usize>,
+    cells: Vec<Vec<T>>,
+}
+
+impl<T: Clone> RectGrid<T> {
+    /// Create a `num_rows` x `num_cols` grid filled with `initial`, able to accept updates to any of
+    /// the given rectangles.
+    /// # Errors
+    /// If any rectangle does not fit within the grid
+    pub fn new<'a, I>(num_rows: usize, num_cols: usize, rectangles: I, initial: T) -> Result<Self>
+    where
+        I: IntoIterator<Item = &'a Rectangle>,
+    {
+        let mut row_bounds = vec![0, num_rows];
+        let mut col_bounds = vec![0, num_cols];
+        for r in rectangles {
+            if r.bottom >= num_rows || r.right >= num_cols {
+                return Err(anyhow!("rectangle {r:?} does not fit in grid"));
+            }
+            row_bounds.extend([r.top, r.bottom + 1]);
+            col_bounds.extend([r.left, r.right + 1]);
+        }
+        for bounds in [&mut row_bounds, &mut col_bounds] {
+            bounds.sort_unstable();
+            bounds.dedup();
+        }
+
+        let cells = vec![vec![initial; col_bounds.len() - 1]; row_bounds.len() - 1];
+        Ok(Self {
+            row_bounds,
+            col_bounds,
+            cells,
+        })
+    }
+
+    /// Set every cell in the rectangle to `val`
+    /// # Errors
+    /// If the rectangle was not one of those the grid was created with
+    pub fn fill(&mut self, rect: &Rectangle, val: &T) -> Result<()> {
+        self.update(rect, |cell| cell.clone_from(val))
+    }
+}
+
+impl<T> RectGrid<T> {
+    /// Apply `f` to every cell in the rectangle
+    /// # Errors
+    /// If the rectangle was not one of those the grid was created with
+    pub fn update<F>(&mut self, rect: &Rectangle, mut f: F) -> Result<()>
+    where
+        F: FnMut(&mut T),
+    {
+        let rows = compressed_range(&self.row_bounds, rect.top, rect.bottom)
+            .ok_or_else(|| anyhow!("unknown rectangle {rect:?}"))?;
+        let cols = compressed_range(&self.col_bounds, rect.left, rect.right)
+            .ok_or_else(|| anyhow!("unknown rectangle {rect:?}"))?;
+
+        for row in self.cells.get_mut(rows).into_iter().flatten() {
+            for cell in row.get_mut(cols.clone()).into_iter().flatten() {
+                f(cell);
+            }
+        }
+        Ok(())
+    }
+
+    /// Number of real cells whose value satisfies `pred`
+    /// # Errors
+    /// If the count overflows a `u64`
+    pub fn count_where<P>(&self, pred: P) -> Result<u64>
+    where
+        P: Fn(&T) -> bool,
+    {
+        self.sum_by(|v| u64::from(pred(v)))
+    }
+
+    /// Sum of `f` over every real cell in the grid
+    /// # Errors
+    /// If the sum overflows a `u64`
+    pub fn sum_by<F>(&self, f: F) -> Result<u64>
+    where
+        F: Fn(&T) -> u64,
+    {
+        let mut total = 0_u64;
+        for (row, height) in self.cells.iter().zip(spans(&self.row_bounds)) {
+            for (cell, width) in row.iter().zip(spans(&self.col_bounds)) {
+                total = f(cell)
+                    .checked_mul(height)
+                    .and_then(|v| v.checked_mul(width))
+                    .and_then(|v| total.checked_add(v))
+                    .ok_or_else(|| anyhow!("sum over the grid overflows u64"))?;
+            }
+        }
+        Ok(total)
+    }
+}
+
+/// Range of compressed indices covering real indices `first..=last`, if both ends lie on
+/// boundaries
+fn compressed_range(bounds: &[usize], first: usize, last: usize) -> Option<std::ops::Range<usize>> {
+    let start = bounds.binary_search(&first).ok()?;
+    let end = bounds.binary_search(&(last + 1)).ok()?;
+    Some(start..end)
+}
+
+/// Number of real indices covered by each compressed index
+fn spans(bounds: &[usize]) -> impl Iterator<Item = u64> + '_ {
+    bounds.windows(2).map(|w| match w {
+        [lo, hi] => (hi - lo) as u64,
+        _ => unreachable!("windows(2) yields pairs"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rect_grid::{RectGrid, Rectangle};
+
+    #[test]
+    fn test_new() {
+        let rects = [Rectangle::new((0, 0), (2, 2))];
+        assert!(RectGrid::new(3, 3, &rects, false).is_ok());
+        assert!(RectGrid::new(2, 3, &rects, false).is_err());
+    }
+
+    #[test]
+    fn test_update() {
+        let rects = [
+            Rectangle::new((0, 0), (9, 9)),
+            Rectangle::new((5, 5), (2, 2)),
+            Rectangle::new((0, 0), (0, 9)),
+        ];
+        let mut grid = RectGrid::new(10, 10, &rects, false).expect("succeeds");
+        assert_eq!(0, grid.count_where(|&v| v).expect("fits"));
+
+        grid.fill(&rects[0], &true).expect("succeeds");
+        assert_eq!(100, grid.count_where(|&v| v).expect("fits"));
+
+        grid.update(&rects[1], |v| *v = !*v).expect("succeeds");
+        assert_eq!(84, grid.count_where(|&v| v).expect("fits"));
+
+        grid.fill(&rects[2], &false).expect("succeeds");
+        assert_eq!(74, grid.count_where(|&v| v).expect("fits"));
+
+        assert!(
+            grid.update(&Rectangle::new((1, 1), (3, 3)), |v| *v = true)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_sum_large() {
+        let rects = [
+            Rectangle::new((0, 0), (999_999, 999_999)),
+            Rectangle::new((0, 0), (499_999, 999_999)),
+        ];
+        let mut grid = RectGrid::new(1_000_000, 1_000_000, &rects, 0u64).expect("succeeds");
+        grid.update(&rects[0], |v| *v += 1).expect("succeeds");
+        grid.update(&rects[1], |v| *v += 2).expect("succeeds");
+        assert_eq!(2_000_000_000_000, grid.sum_by(|&v| v).expect("fits"));
+        assert_eq!(
+            rects[1].area(),
+            grid.count_where(|&v| v == 3).expect("fits")
+        );
+    }
+
+    #[test]
+    fn test_sum_overflow() {
+        let all = Rectangle::new((0, 0), (999_999, 999_999));
+        let grid = RectGrid::new(1_000_000, 1_000_000, &[all], u64::MAX / 1000).expect("succeeds");
+        assert!(grid.sum_by(|&v| v).is_err());
+        assert_eq!(1_000_000_000_000, grid.count_where(|_| true).expect("fits"));
+    }
+}
@@ -1,12 +1,16 @@
 use adv_code_2025::grids::{Grid, Position};
 use adv_code_2025::start_day;
+use adv_code_2025::visualize::{Format, FrameWriter, Image, Rgb};
 use anyhow::{Ok, Result};
 use const_format::concatcp;
+use std::collections::HashSet;
 use std::io::{BufRead, BufReader};
+use std::path::Path;
 use std::time::Instant;
 
 const DAY: &str = "04";
 const INPUT_FILE: &str = concatcp!("input/", DAY, ".txt");
+const FRAMES_DIR: &str = concatcp!("output/", DAY);
 
 fn parse_grid<R: BufRead>(reader: R) -> Result<Grid<char>> {
     let vec: Result<Vec<Vec<char>>> = reader
@@ -43,37 +47,67 @@ fn part1<R: BufRead>(reader: R) -> Result<usize> {
         .sum())
 }
 
-fn part2<R: BufRead>(reader: R) -> Result<usize> {
-    let mut grid = parse_grid(reader)?;
-
-    let mut stack: Vec<_> = grid
-        .all_positions()
-        .map(|pos| (grid.get(&pos), pos))
-        .filter_map(|(val, pos)| match val {
-            None => unreachable!("all_positions should only return valid positions"),
-            Some('@') if count_adjacent_rolls(&grid, &pos) < 4 => Some(pos),
-            _ => None,
-        })
-        .collect();
-
+/// Remove every roll that can be reached at once, over and over until none can. `on_wave` sees
+/// the grid and the rolls about to go before each wave, and once more with none when it's done.
+/// Returns how many rolls were removed in all.
+fn remove_in_waves<F>(grid: &mut Grid<char>, mut on_wave: F) -> Result<usize>
+where
+    F: FnMut(&Grid<char>, &[Position]) -> Result<()>,
+{
+    let mut candidates: HashSet<Position> = grid.all_positions().collect();
     let mut num_removed = 0;
-    while let Some(to_process) = stack.pop() {
-        if let Some('@') = grid.get(&to_process)
-            && count_adjacent_rolls(&grid, &to_process) < 4
-        {
-            grid.set(&to_process, '.')?;
-            num_removed += 1;
-            stack.extend(to_process.adjacent());
+    loop {
+        let wave: Vec<Position> = candidates
+            .drain()
+            .filter(|pos| grid.get(pos) == Some(&'@') && count_adjacent_rolls(grid, pos) < 4)
+            .collect();
+        on_wave(grid, &wave)?;
+        if wave.is_empty() {
+            return Ok(num_removed);
+        }
+        for pos in &wave {
+            grid.set(pos, '.')?;
         }
+        num_removed += wave.len();
+        // Only rolls next to one just removed can have become reachable
+        candidates.extend(wave.iter().flat_map(Position::adjacent));
     }
+}
 
-    Ok(num_removed)
+fn part2<R: BufRead>(reader: R) -> Result<usize> {
+    remove_in_waves(&mut parse_grid(reader)?, |_, _| Ok(()))
+}
+
+fn roll_color(c: &char) -> Rgb {
+    match c {
+        '@' => Rgb(200, 160, 60),
+        'x' => Rgb(220, 40, 40),
+        _ => Rgb::BLACK,
+    }
+}
+
+/// Write a frame per wave of removals from part 2, each showing the rolls about to go in red
+fn write_frames<R: BufRead>(reader: R, frames: &mut FrameWriter) -> Result<usize> {
+    remove_in_waves(&mut parse_grid(reader)?, |grid, wave| {
+        let mut marked = grid.clone();
+        for pos in wave {
+            marked.set(pos, 'x')?;
+        }
+        frames.write(&Image::from_grid(&marked, roll_color))?;
+        Ok(())
+    })
 }
 
 fn main() -> Result<()> {
     let input = std::fs::read(INPUT_FILE)?;
     start_day(DAY);
 
+    if std::env::args().any(|arg| arg == "--frames") {
+        let mut frames = FrameWriter::new(Path::new(FRAMES_DIR), Format::Png, 4)?;
+        write_frames(BufReader::new(input.as_slice()), &mut frames)?;
+        println!("Wrote {} frames to {FRAMES_DIR}\n", frames.frames_written());
+    }
+
     println!("=== Part 1 ===");
     let p1_time = Instant::now();
     let input_file = BufReader::new(input.as_slice());
@@ -123,4 +157,22 @@ mod tests {
         let result = part2(BufReader::new(TEST.as_bytes()));
         assert_eq!(result.unwrap(), expected)
     }
+
+    #[test]
+    fn frames() {
+        let dir = std::env::temp_dir().join(format!("aoc-{DAY}-frames-{}", std::process::id()));
+        let mut frames = FrameWriter::new(&dir, Format::Ppm, 1).expect("succeeds");
+        let removed = write_frames(BufReader::new(TEST.as_bytes()), &mut frames);
+        assert_eq!(43, removed.unwrap());
+        // The first wave removes the 13 rolls from part 1
+        let first = std::fs::read(dir.join("frame_00000.ppm")).expect("frame exists");
+        let pixels = first.strip_prefix(b"P6\n10 10\n255\n").expect("10x10 ppm");
+        let red = pixels.chunks(3).filter(|p| *p == [220, 40, 40]).count();
+        assert_eq!(13, red);
+        assert!(
+            dir.join(format!("frame_{:05}.ppm", frames.frames_written() - 1))
+                .exists()
+        );
+        std::fs::remove_dir_all(&dir).expect("cleanup succeeds");
+    }
 }
@@ -1,3 +1,4 @@
+use adv_code_2025::grids::Grid;
 use adv_code_2025::start_day;
 use anyhow::{Result, anyhow};
 use const_format::concatcp;
@@ -56,20 +57,15 @@ impl FromStr for Operation {
 // Given reader of input file, transpose it so that inner vectors contain
 // entire equations, the outer vector is a collection of all equations
 fn group_equations<R: BufRead>(reader: R) -> Result<Vec<Vec<String>>> {
-    let mut transposed: Vec<Vec<String>> = Vec::new();
-    for res in reader.lines() {
-        let line = res?;
-        line.split_whitespace().enumerate().for_each(|(idx, s)| {
-            if transposed.len() <= idx {
-                transposed.push(Vec::new());
-            }
-            transposed
-                .get_mut(idx)
-                .expect("inner vector exists via above")
-                .push(s.to_string());
-        })
-    }
-    Ok(transposed)
+    let tokens: Vec<Vec<String>> = reader
+        .lines()
+        .map(|line| line.map(|s| s.split_whitespace().map(str::to_string).collect()))
+        .collect::<Result<_, _>>()?;
+    Ok(Grid::new(tokens)
+        .transpose()
+        .rows()
+        .map(<[String]>::to_vec)
+        .collect())
 }
 
 fn part1_calc(equations: Vec<Vec<String>>) -> Result<u64> {
@@ -99,38 +95,29 @@ fn part2<R: BufRead>(reader: R) -> Result<u64> {
         .lines()
         .map(|line| line.map(|s| s.chars().collect()))
         .collect::<Result<Vec<_>, _>>()?;
-    if input.is_empty() {
-        return Ok(0);
-    }
-
-    let max_cols = input
-        .iter()
-        .map(|v| v.len())
-        .max()
-        .expect("at least one row in input");
+    // Pad so every column lines up, then read columns as rows
+    let columns = Grid::from_jagged(input, ' ').transpose();
 
     let mut curr_nums: Vec<u64> = Vec::new();
     let mut curr = 0;
     let mut total = 0;
     // Iterate right-to-left since operations are in bottom-left corners
-    'outer: for col in (0..max_cols).rev() {
-        for row in &input {
-            if let Some(c) = row.get(col) {
-                match c {
-                    '0'..='9' => {
-                        let v = c.to_digit(10).expect("known digit");
-                        curr = (curr * 10) + v as u64;
-                    }
-                    '+' | '*' => {
-                        curr_nums.push(curr);
-                        let op = Operation::from_char(c)?;
-                        total += op.apply_on_iter(curr_nums.iter());
-                        curr_nums.clear();
-                        curr = 0;
-                        continue 'outer;
-                    }
-                    _ => {}
+    'outer: for column in columns.rows().rev() {
+        for c in column {
+            match c {
+                '0'..='9' => {
+                    let v = c.to_digit(10).expect("known digit");
+                    curr = (curr * 10) + v as u64;
+                }
+                '+' | '*' => {
+                    curr_nums.push(curr);
+                    let op = Operation::from_char(c)?;
+                    total += op.apply_on_iter(curr_nums.iter());
+                    curr_nums.clear();
+                    curr = 0;
+                    continue 'outer;
                 }
+                _ => {}
             }
         }
         // End of column
@@ -1,9 +1,9 @@
+use adv_code_2025::polygon::RectilinearPolygon;
 use adv_code_2025::start_day;
 use anyhow::{Context, Result, anyhow};
 use const_format::concatcp;
 use itertools::Itertools;
 use regex::Regex;
-use std::collections::HashMap;
 use std::io::{BufRead, BufReader};
 use std::str::FromStr;
 use std::sync::LazyLock;
@@ -52,130 +52,14 @@ fn part1(coordinates: &[Coordinate]) -> Result<u64> {
         .ok_or_else(|| anyhow!("no max exists, not enough elements given"))
 }
 
-#[derive(Default)]
-struct CompressedGrid {
-    x_remap: HashMap<u64, usize>,
-    y_remap: HashMap<u64, usize>,
-    grid: Vec<Vec<char>>,
-}
-
-impl CompressedGrid {
-    // We do a bunch of indexing which will succeed by construction. Using `.get(..).expect` gets very verbose
-    #[allow(clippy::indexing_slicing)]
-    fn new(coordinates: &[Coordinate]) -> Self {
-        if coordinates.is_empty() {
-            return Self::default();
-        }
-
-        // Compress coordinates. Leave space in between so we can distinguish
-        // #.#      ##
-        // ...  and ##
-        // #.#
-        let x_remap: HashMap<_, _> = coordinates
-            .iter()
-            .map(|c| c.x)
-            .sorted()
-            .unique()
-            .enumerate()
-            .map(|(idx, x)| (x, 2 * idx))
-            .collect();
-        let y_remap: HashMap<_, _> = coordinates
-            .iter()
-            .map(|c| c.y)
-            .sorted()
-            .unique()
-            .enumerate()
-            .map(|(idx, y)| (y, 2 * idx))
-            .collect();
-
-        let max_y = *y_remap.values().max().expect("at least one y");
-        let max_x = *x_remap.values().max().expect("at least one x");
-        let mut grid = vec![vec!['.'; max_x + 1]; max_y + 1];
-
-        // Add the vertices
-        for c in coordinates {
-            let (mapped_x, mapped_y) = (x_remap[&c.x], y_remap[&c.y]);
-            grid[mapped_y][mapped_x] = '#';
-        }
-
-        // Connect adjacent corners
-        for (c1, c2) in coordinates.iter().circular_tuple_windows() {
-            if c1.x == c2.x {
-                let x = x_remap[&c1.x];
-                let (y1, y2) = (y_remap[&c1.y], y_remap[&c2.y]);
-                let (min_y, max_y) = (y1.min(y2), y1.max(y2));
-                // I find the range-based looping easier to read than getting rows/indexing into them
-                #[allow(clippy::needless_range_loop)]
-                for y in min_y + 1..max_y {
-                    grid[y][x] = 'X';
-                }
-            } else {
-                let y = y_remap[&c1.y];
-                let (x1, x2) = (x_remap[&c1.x], x_remap[&c2.x]);
-                let (min_x, max_x) = (x1.min(x2), x1.max(x2));
-
-                let row = grid.get_mut(y).expect("row exists by construction");
-                for cell in row.iter_mut().take(max_x).skip(min_x + 1) {
-                    *cell = 'X';
-                }
-            }
-        }
-
-        // Fill in the rest
-        for row in grid.iter_mut() {
-            let mut fill = false;
-            for cell in row.iter_mut() {
-                match cell {
-                    'X' | '#' => {
-                        fill = !fill;
-                    }
-                    '.' => {
-                        if fill {
-                            *cell = 'X';
-                        }
-                    }
-                    _ => unreachable!("no other chars in grid"),
-                }
-            }
-        }
-
-        Self {
-            x_remap,
-            y_remap,
-            grid,
-        }
-    }
-}
-
-// Similar to above, the indexing here gets quite verbose. As long as compressed was constructed
-// with both a and b these gets will not panic
-#[allow(clippy::indexing_slicing)]
-fn valid_corners(a: &Coordinate, b: &Coordinate, compressed: &CompressedGrid) -> bool {
-    let (min_x, max_x) = (a.x.min(b.x), a.x.max(b.x));
-    let (min_y, max_y) = (a.y.min(b.y), a.y.max(b.y));
-    let (min_x_remap, max_x_remap) = (compressed.x_remap[&min_x], compressed.x_remap[&max_x]);
-    let (min_y_remap, max_y_remap) = (compressed.y_remap[&min_y], compressed.y_remap[&max_y]);
-
-    !compressed
-        .grid
-        .iter()
-        .take(max_y_remap + 1)
-        .skip(min_y_remap)
-        .any(|row| {
-            row.iter()
-                .take(max_x_remap + 1)
-                .skip(min_x_remap)
-                .any(|&cell| cell == '.')
-        })
-}
-
 fn part2(coordinates: &[Coordinate]) -> Result<u64> {
-    let compressed = CompressedGrid::new(coordinates);
+    let polygon = RectilinearPolygon::new(coordinates.iter().map(|c| (c.x, c.y)))?;
 
     coordinates
         .iter()
         .tuple_combinations()
-        .filter_map(|(a, b)| valid_corners(a, b, &compressed).then_some(enclosed_area(a, b)))
+        .filter(|(a, b)| polygon.contains_rectangle((a.x, a.y), (b.x, b.y)))
+        .map(|(a, b)| enclosed_area(a, b))
         .max()
         .ok_or_else(|| anyhow!("no suitable rectangle exists"))
 }
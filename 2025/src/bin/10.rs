@@ -1,9 +1,8 @@
+use adv_code_2025::linear::LinearSystem;
+use adv_code_2025::linear::gf2::{BitMatrix, BitVector};
 use adv_code_2025::start_day;
 use anyhow::{Result, anyhow};
 use const_format::concatcp;
-use good_lp::microlp;
-use good_lp::{Solution, SolverModel, variable, variables};
-use std::collections::{HashSet, VecDeque};
 use std::io::BufReader;
 use std::time::Instant;
 
@@ -16,15 +15,6 @@ enum Light {
     On,
 }
 
-impl Light {
-    fn toggle(self) -> Self {
-        match self {
-            Self::Off => Self::On,
-            Self::On => Self::Off,
-        }
-    }
-}
-
 #[derive(Hash, PartialEq, Eq)]
 struct Button {
     toggled_lights: Vec<usize>,
@@ -98,76 +88,62 @@ mod parse {
     }
 }
 
-fn min_p1_button_pushes(m: &Machine) -> Result<u64> {
-    let mut seen: HashSet<Vec<Light>> = HashSet::new();
-    let mut queue: VecDeque<(Vec<Light>, u64)> = VecDeque::new();
-    let initial = vec![Light::Off; m.goal_state.len()];
-
-    queue.push_back((initial, 0));
-    while let Some((state, so_far)) = queue.pop_front() {
-        if state == m.goal_state {
-            return Ok(so_far);
-        }
-
-        for b in &m.buttons {
-            let mut next = state.clone();
-            for to_toggle in &b.toggled_lights {
-                let light = next.get_mut(*to_toggle).expect("button valid");
-                *light = light.toggle();
-            }
-            if !seen.contains(&next) {
-                seen.insert(next.clone());
-                queue.push_back((next, so_far + 1));
-            }
-        }
-    }
+/// Buttons to push to reach the goal lights in the fewest pushes. Pushing a button twice undoes
+/// it, so each is pushed at most once and the lights left on are a sum over GF(2).
+fn p1_button_pushes(m: &Machine) -> Result<BitVector> {
+    let num_lights = m.goal_state.len();
+    let columns = m
+        .buttons
+        .iter()
+        .map(|b| BitVector::from_ones(num_lights, b.toggled_lights.iter().copied()))
+        .collect::<Result<Vec<_>>>()?;
+    let on = m
+        .goal_state
+        .iter()
+        .enumerate()
+        .filter(|&(_, &l)| l == Light::On)
+        .map(|(i, _)| i);
+    let goal = BitVector::from_ones(num_lights, on)?;
+    BitMatrix::from_columns(num_lights, &columns)?
+        .solve(&goal)?
+        .ok_or_else(|| anyhow!("unable to reach goal state with any number of button presses"))?
+        .min_weight()
+}
 
-    Err(anyhow!(
-        "unable to reach goal state with any number of button presses"
-    ))
+fn min_p1_button_pushes(m: &Machine) -> Result<u64> {
+    Ok(u64::try_from(p1_button_pushes(m)?.count_ones())?)
 }
 
 fn part1(machines: &[Machine]) -> Result<u64> {
     machines.iter().map(min_p1_button_pushes).sum()
 }
 
-fn min_p2(m: &Machine) -> Result<u32> {
-    // Use an LP solver (microlp) to solve, my "best" normal solution was painfully slow
-    let mut vars = variables!();
-
-    // Each variable, i, corresponds to the number of times button i is pressed
-    let counts: Vec<_> = (0..m.buttons.len())
-        .map(|_| vars.add(variable().min(0).integer()))
+/// How many times to push each button to reach the joltage requirements in the fewest pushes
+fn p2_button_pushes(m: &Machine) -> Result<Vec<u64>> {
+    // One equation per counter: the pushes of the buttons wired to it add up to its requirement
+    let rows = (0..m.joltage_requirements.len())
+        .map(|jolt_idx| {
+            m.buttons
+                .iter()
+                .map(|b| i64::from(b.toggled_lights.contains(&jolt_idx)))
+                .collect()
+        })
         .collect();
-
-    // Objective: Minimize sum of all counts (total button presses)
-    let objective = counts.iter().sum::<good_lp::Expression>();
-    let mut problem = vars.minimise(objective).using(microlp);
-
-    // Constraints: In the end, we must get the required joltage vector
-    for (jolt_idx, &target_val) in m.joltage_requirements.iter().enumerate() {
-        let mut row_expr = good_lp::Expression::from(0);
-        for (btn_idx, btn) in m.buttons.iter().enumerate() {
-            if btn.toggled_lights.contains(&jolt_idx) {
-                row_expr += counts.get(btn_idx).expect("button indeces are valid");
-            }
-        }
-
-        problem.add_constraint(row_expr.eq(target_val));
-    }
-
-    let solution = problem
-        .solve()
-        .map_err(|e| anyhow!("Solver error: {}", e))?;
-
-    let total_presses = solution.eval(counts.iter().sum::<good_lp::Expression>());
-    // total_presses is float type, but we have constrained to be integer above
-    // so conversion should succeed
-    Ok(total_presses as u32)
+    let rhs = m
+        .joltage_requirements
+        .iter()
+        .map(|&j| i64::from(j))
+        .collect();
+    LinearSystem::new(rows, rhs)?
+        .min_sum_solution()?
+        .ok_or_else(|| anyhow!("no combination of button pushes reaches the joltage requirements"))
 }
 
-fn part2(machines: &[Machine]) -> Result<u32> {
-    machines.iter().map(min_p2).sum()
+fn part2(machines: &[Machine]) -> Result<u64> {
+    machines
+        .iter()
+        .map(|m| Ok(p2_button_pushes(m)?.iter().sum::<u64>()))
+        .sum()
 }
 
 fn main() -> Result<()> {
@@ -227,4 +203,31 @@ mod tests {
         let result = part2(&machines);
         assert_eq!(result.unwrap(), expected)
     }
+
+    #[test]
+    fn p1_pushes() {
+        let machines = parse::parse(BufReader::new(TEST.as_bytes())).expect("parse succeeds");
+        let m = machines.get(2).expect("three machines");
+        // Only the second and third buttons are needed
+        let pushes = p1_button_pushes(m).expect("reachable");
+        assert_eq!(vec![1, 2], pushes.ones().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn p2_pushes() {
+        let machines = parse::parse(BufReader::new(TEST.as_bytes())).expect("parse succeeds");
+        let m = machines.first().expect("three machines");
+        let pushes = p2_button_pushes(m).expect("reachable");
+        assert_eq!(10, pushes.iter().sum::<u64>());
+        for (jolt_idx, &required) in m.joltage_requirements.iter().enumerate() {
+            let total: u64 = m
+                .buttons
+                .iter()
+                .zip(&pushes)
+                .filter(|(b, _)| b.toggled_lights.contains(&jolt_idx))
+                .map(|(_, &n)| n)
+                .sum();
+            assert_eq!(u64::from(required), total);
+        }
+    }
 }
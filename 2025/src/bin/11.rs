@@ -1,3 +1,6 @@
+use adv_code_2025::graph::dag::count_paths;
+use adv_code_2025::graph::dot::Highlight;
+use adv_code_2025::graph::{Direction, Graph, Storage};
 use adv_code_2025::start_day;
 use anyhow::{Result, anyhow};
 use const_format::concatcp;
@@ -5,15 +8,14 @@ use nom::character::complete::{alpha1, char, multispace0};
 use nom::multi::many0;
 use nom::sequence::{delimited, terminated};
 use nom::{Finish, IResult, Parser};
-use std::collections::{HashMap, HashSet, VecDeque};
 use std::io::{BufRead, BufReader};
-use std::ops;
 use std::time::Instant;
 
 const DAY: &str = "11";
 const INPUT_FILE: &str = concatcp!("input/", DAY, ".txt");
+const DOT_FILE: &str = concatcp!("output/", DAY, ".dot");
 
-type Graph = HashMap<String, HashSet<String>>;
+type Devices = Graph<()>;
 
 fn parse_line(input: &str) -> IResult<&str, (&str, Vec<&str>)> {
     let (input, node) = terminated(alpha1, char(':')).parse(input)?;
@@ -21,120 +23,40 @@ fn parse_line(input: &str) -> IResult<&str, (&str, Vec<&str>)> {
     Ok((input, (node, neighbors)))
 }
 
-fn parse<R: BufRead>(reader: R) -> Result<Graph> {
-    let mut g = HashMap::new();
+fn parse<R: BufRead>(reader: R) -> Result<Devices> {
+    let mut g = Graph::new(Direction::Directed, Storage::AdjacencyList);
     for line in reader.lines() {
         let l = line?;
         let (_, (node, neighbors)) = parse_line
             .parse(&l)
             .finish()
             .map_err(|e| anyhow!("Parse error: {}", e))?;
-        let node_owned = node.to_string();
-        let adj: &mut HashSet<String> = g.entry(node_owned).or_default();
-        adj.extend(neighbors.iter().map(|n| n.to_string()));
+        g.add_node(node);
         for nbr in neighbors {
-            g.entry(nbr.to_string()).or_default();
+            g.add_edge(node, nbr, ());
         }
     }
     Ok(g)
 }
 
-fn part1(g: &Graph) -> usize {
-    let mut num_ways = HashMap::new();
-    let mut queue = VecDeque::new();
-
-    let start_label = "you";
-    num_ways.insert(start_label, 1);
-    queue.push_back(start_label);
-    while let Some(node) = queue.pop_front() {
-        let n = num_ways.get(node).copied().unwrap_or_default();
-        if n == 0 {
-            continue;
-        }
-        if let Some(neighbors) = g.get(node) {
-            if neighbors.is_empty() {
-                continue;
-            }
-            for nbr in neighbors {
-                *num_ways.entry(nbr).or_insert(0) += n;
-                queue.push_back(nbr);
-            }
-            num_ways.insert(node, 0);
-        }
-    }
-
-    num_ways.get("out").copied().unwrap_or_default()
+fn device(g: &Devices, name: &str) -> Result<usize> {
+    g.id(name).ok_or_else(|| anyhow!("no device named {name}"))
 }
 
-#[derive(Debug, Clone, Copy, Default)]
-// One insight: Rather than storing exactly which of "dac" and "fft" each path has seen,
-// we can just store how many. If there are no cycles, then in valid paths seeing two
-// means we've seen both.
-struct P2Ways {
-    pass_neither: usize,
-    pass_one: usize,
-    pass_both: usize,
+fn part1(g: &Devices) -> Result<u128> {
+    count_paths(g, device(g, "you")?, device(g, "out")?, &[], &[])
 }
 
-impl P2Ways {
-    fn pass_special(&mut self) {
-        *self = Self {
-            pass_neither: 0,
-            pass_one: self.pass_neither,
-            pass_both: self.pass_one + self.pass_both,
-        }
-    }
-
-    fn is_zero(&self) -> bool {
-        self.pass_both == 0 && self.pass_one == 0 && self.pass_neither == 0
-    }
+fn part2(g: &Devices) -> Result<u128> {
+    let required = [device(g, "dac")?, device(g, "fft")?];
+    count_paths(g, device(g, "svr")?, device(g, "out")?, &required, &[])
 }
 
-impl ops::AddAssign for P2Ways {
-    fn add_assign(&mut self, rhs: Self) {
-        self.pass_neither += rhs.pass_neither;
-        self.pass_one += rhs.pass_one;
-        self.pass_both += rhs.pass_both;
-    }
-}
-
-fn part2(g: &Graph) -> usize {
-    let mut num_ways = HashMap::new();
-    let mut queue = VecDeque::new();
-
-    let start_label = "svr";
-    num_ways.insert(
-        start_label,
-        P2Ways {
-            pass_neither: 1,
-            pass_one: 0,
-            pass_both: 0,
-        },
-    );
-    queue.push_back(start_label);
-    while let Some(node) = queue.pop_front() {
-        let mut n = num_ways.get(node).copied().unwrap_or_default();
-        if n.is_zero() {
-            continue;
-        }
-
-        if node == "dac" || node == "fft" {
-            n.pass_special();
-        }
-
-        if let Some(neighbors) = g.get(node) {
-            if neighbors.is_empty() {
-                continue;
-            }
-            for nbr in neighbors {
-                *num_ways.entry(nbr).or_default() += n;
-                queue.push_back(nbr);
-            }
-            num_ways.insert(node, P2Ways::default());
-        }
-    }
-
-    num_ways.get("out").copied().unwrap_or_default().pass_both
+/// The device graph in DOT format, with the devices the puzzle cares about highlighted
+fn to_dot(g: &Devices) -> String {
+    let landmarks = ["you", "svr", "dac", "fft", "out"];
+    let highlight = Highlight::nodes(landmarks.iter().filter_map(|name| g.id(name)));
+    g.to_dot(&highlight, |()| None)
 }
 
 fn main() -> Result<()> {
@@ -146,15 +68,21 @@ fn main() -> Result<()> {
     let input = parse(BufReader::new(file.as_slice()))?;
     println!("Parsing time = {:.2?}\n", parse_time.elapsed());
 
+    if std::env::args().any(|arg| arg == "--dot") {
+        std::fs::create_dir_all("output")?;
+        std::fs::write(DOT_FILE, to_dot(&input))?;
+        println!("Wrote {DOT_FILE}\n");
+    }
+
     println!("=== Part 1 ===");
     let p1_time = Instant::now();
-    let result = part1(&input);
+    let result = part1(&input)?;
     println!("Result = {}", result);
     println!("Elapsed = {:.2?}", p1_time.elapsed());
 
     println!("\n=== Part 2 ===");
     let p2_time = Instant::now();
-    let result = part2(&input);
+    let result = part2(&input)?;
     println!("Result = {}", result);
     println!("Elapsed = {:.2?}", p2_time.elapsed());
 
@@ -203,7 +131,7 @@ hhh: out
     fn part_1() {
         let expected = 5;
         let input = super::parse(BufReader::new(TEST.as_bytes())).expect("parse succeeds");
-        let result = part1(&input);
+        let result = part1(&input).expect("part1 succeeds");
         assert_eq!(result, expected)
     }
 
@@ -211,7 +139,25 @@ hhh: out
     fn part_2() {
         let expected = 2;
         let input = super::parse(BufReader::new(TEST_PART_2.as_bytes())).expect("parse succeeds");
-        let result = part2(&input);
+        let result = part2(&input).expect("part2 succeeds");
         assert_eq!(result, expected)
     }
+
+    #[test]
+    fn part1_rejects_cycles() {
+        let looped = "you: aaa\naaa: bbb\nbbb: aaa out\n";
+        let input = super::parse(BufReader::new(looped.as_bytes())).expect("parse succeeds");
+        let err = part1(&input).expect_err("cyclic");
+        assert_eq!("graph has cycles through [aaa, bbb]", err.to_string());
+    }
+
+    #[test]
+    fn dot() {
+        let input = super::parse(BufReader::new(TEST_PART_2.as_bytes())).expect("parse succeeds");
+        let dot = to_dot(&input);
+        assert!(dot.contains("\"dac\" [style=filled, fillcolor=gold];"));
+        assert!(dot.contains("\"ccc\";"));
+        assert!(dot.contains("\"svr\" -> \"aaa\";"));
+        assert_eq!(16, dot.matches(" -> ").count());
+    }
 }
@@ -1,25 +1,14 @@
+use adv_code_2025::grids::packing::{Packing, Polyomino, pack};
 use adv_code_2025::start_day;
 use anyhow::Result;
 use const_format::concatcp;
 use parse::parse;
 use std::io::BufReader;
-use std::ops::Range;
 use std::time::Instant;
 
 const DAY: &str = "12";
 const INPUT_FILE: &str = concatcp!("input/", DAY, ".txt");
-
-struct Shape {
-    occupied_spaces: usize,
-}
-
-impl Shape {
-    fn new(shape: Vec<Vec<bool>>) -> Self {
-        Self {
-            occupied_spaces: shape.iter().flatten().filter(|&&b| b).count(),
-        }
-    }
-}
+const ARRANGEMENTS_FILE: &str = concatcp!("output/", DAY, ".txt");
 
 struct Region {
     length: usize,
@@ -28,12 +17,14 @@ struct Region {
 }
 
 struct Input {
-    shapes: Vec<Shape>,
+    shapes: Vec<Polyomino>,
     regions: Vec<Region>,
 }
 
 mod parse {
-    use super::{Input, Region, Shape};
+    use super::{Input, Region};
+    use adv_code_2025::grids::Grid;
+    use adv_code_2025::grids::packing::Polyomino;
     use anyhow::{Result, anyhow};
     use nom::Finish;
     use nom::IResult;
@@ -65,6 +56,10 @@ mod parse {
             return Err(anyhow!("Failed to parse entire input"));
         }
 
+        let shapes = shapes
+            .into_iter()
+            .map(|cells| Polyomino::new(&Grid::new(cells)))
+            .collect::<Result<_>>()?;
         Ok(Input { shapes, regions })
     }
 
@@ -72,16 +67,15 @@ mod parse {
         terminated(usize, tag(":\n")).parse(input)
     }
 
-    fn shape(input: &str) -> IResult<&str, Shape> {
-        let (input, shape) = count(
+    fn shape(input: &str) -> IResult<&str, Vec<Vec<bool>>> {
+        count(
             terminated(
                 count(alt((value(true, char('#')), value(false, char('.')))), 3),
                 newline,
             ),
             3,
         )
-        .parse(input)?;
-        Ok((input, Shape::new(shape)))
+        .parse(input)
     }
 
     fn region(input: &str) -> IResult<&str, Region> {
@@ -101,38 +95,31 @@ mod parse {
     }
 }
 
-// True if there are enough 3x3s in the grid for all shapes to get one
-fn region_definitely_possible(r: &Region) -> bool {
-    let num_shapes: usize = r.requirements.iter().sum();
-    let num_boxes = (r.length / 3) * (r.width / 3);
-    num_shapes <= num_boxes
+/// A way to fit the region's presents, if there is one. The region's length runs across.
+fn arrangement(r: &Region, shapes: &[Polyomino]) -> Result<Option<Packing>> {
+    pack(r.width, r.length, shapes, &r.requirements)
 }
 
-// True if there are at least enough empty spaces for the shapes to
-// occupy (if we could cut the shapes up)
-fn region_maybe_possible(r: &Region, shapes: &[Shape]) -> bool {
-    let min_required_spaces: usize = r
-        .requirements
-        .iter()
-        .zip(shapes)
-        .map(|(n, shape)| n * shape.occupied_spaces)
-        .sum();
-    r.length * r.width >= min_required_spaces
+/// Every region with a drawing of how its presents fit, or a note that they don't
+fn render_arrangements(input: &Input) -> Result<String> {
+    let mut out = String::new();
+    for r in &input.regions {
+        out.push_str(&format!("{}x{}:\n", r.length, r.width));
+        match arrangement(r, &input.shapes)? {
+            Some(packing) => out.push_str(&packing.render()),
+            None => out.push_str("does not fit\n"),
+        }
+        out.push('\n');
+    }
+    Ok(out)
 }
 
-fn part1(input: &Input) -> Result<Range<usize>> {
-    let min = input
+fn part1(input: &Input) -> Result<usize> {
+    input
         .regions
         .iter()
-        .filter(|r| region_definitely_possible(r))
-        .count();
-    let max = input
-        .regions
-        .iter()
-        .filter(|r| region_maybe_possible(r, &input.shapes))
-        .count();
-
-    Ok(min..max)
+        .map(|r| Ok(usize::from(arrangement(r, &input.shapes)?.is_some())))
+        .sum()
 }
 
 fn main() -> Result<()> {
@@ -144,10 +131,16 @@ fn main() -> Result<()> {
     let input = parse(BufReader::new(file.as_slice()))?;
     println!("Parsing time = {:.2?}\n", parse_time.elapsed());
 
+    if std::env::args().any(|arg| arg == "--show") {
+        std::fs::create_dir_all("output")?;
+        std::fs::write(ARRANGEMENTS_FILE, render_arrangements(&input)?)?;
+        println!("Wrote {ARRANGEMENTS_FILE}\n");
+    }
+
     println!("=== Part 1 ===");
     let p1_time = Instant::now();
     let result = part1(&input)?;
-    println!("Result in range: {} - {}", result.start, result.end);
+    println!("Result = {result}");
     println!("Elapsed = {:.2?}", p1_time.elapsed());
 
     Ok(())
@@ -155,7 +148,7 @@ fn main() -> Result<()> {
 
 #[cfg(test)]
 mod tests {
-    use super::{parse as base_parse, part1};
+    use super::{parse as base_parse, part1, render_arrangements};
     use std::io::BufReader;
 
     const TEST: &str = "\
@@ -205,9 +198,27 @@ mod tests {
 
     #[test]
     fn part_1() {
-        let expected = 0..3;
+        let expected = 2;
         let input = base_parse::parse(BufReader::new(TEST.as_bytes())).expect("parse succeeds");
         let result = part1(&input);
         assert_eq!(result.unwrap(), expected);
     }
+
+    #[test]
+    fn arrangements() {
+        let input = base_parse::parse(BufReader::new(TEST.as_bytes())).expect("parse succeeds");
+        let rendered = render_arrangements(&input).expect("valid regions");
+        let blocks: Vec<_> = rendered.split("\n\n").collect();
+        assert!(blocks.first().is_some_and(|b| b.starts_with("4x4:\n")));
+        // Each drawing is a line per row of the region, along its length
+        let rows: Vec<_> = blocks
+            .get(1)
+            .expect("second region")
+            .lines()
+            .skip(1)
+            .collect();
+        assert_eq!(5, rows.len());
+        assert!(rows.iter().all(|r| r.len() == 12));
+        assert_eq!(Some(&"12x5:\ndoes not fit"), blocks.get(2));
+    }
 }
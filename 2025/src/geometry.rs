@@ -0,0 +1,206 @@
+use crate::grids::point::Point;
+use itertools::Itertools;
+
+/// Which way a sequence of points turns
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Orientation {
+    /// Counter-clockwise with the y axis pointing up, so clockwise on screen where y points down
+    CounterClockwise,
+    Clockwise,
+    Collinear,
+}
+
+impl Orientation {
+    fn from_cross(cross: i128) -> Self {
+        match cross.signum() {
+            1 => Orientation::CounterClockwise,
+            -1 => Orientation::Clockwise,
+            _ => Orientation::Collinear,
+        }
+    }
+}
+
+/// Which way the path a -> b -> c turns at b
+#[must_use]
+pub fn turn(a: &Point<2>, b: &Point<2>, c: &Point<2>) -> Orientation {
+    Orientation::from_cross(cross(a, b, c))
+}
+
+/// Twice the signed area of the polygon with the given vertices in order (the shoelace formula).
+/// Doubled so it is always an integer, positive when the vertices run counter-clockwise.
+#[must_use]
+pub fn double_signed_area(vertices: &[Point<2>]) -> i128 {
+    vertices
+        .iter()
+        .circular_tuple_windows()
+        .map(|(a, b)| i128::from(a.x()) * i128::from(b.y()) - i128::from(b.x()) * i128::from(a.y()))
+        .sum()
+}
+
+/// Area of the polygon with the given vertices in order, halved from [`double_signed_area`]
+#[must_use]
+pub fn area(vertices: &[Point<2>]) -> f64 {
+    double_signed_area(vertices).unsigned_abs() as f64 / 2.0
+}
+
+/// Direction the vertices of a polygon run in
+#[must_use]
+pub fn orientation(vertices: &[Point<2>]) -> Orientation {
+    Orientation::from_cross(double_signed_area(vertices))
+}
+
+/// Euclidean length of the polygon's boundary
+#[must_use]
+pub fn perimeter(vertices: &[Point<2>]) -> f64 {
+    vertices
+        .iter()
+        .circular_tuple_windows()
+        .map(|(a, b)| (a.squared_euclidean_distance(b) as f64).sqrt())
+        .sum()
+}
+
+/// Number of integer points lying on the polygon's boundary. For a polygon with only horizontal
+/// and vertical edges this is also its perimeter.
+#[must_use]
+pub fn boundary_points(vertices: &[Point<2>]) -> u64 {
+    vertices
+        .iter()
+        .circular_tuple_windows()
+        .map(|(a, b)| gcd(a.x().abs_diff(b.x()), a.y().abs_diff(b.y())))
+        .sum()
+}
+
+/// Number of integer points strictly inside a simple polygon, by Pick's theorem
+/// `A = i + b/2 - 1`
+#[must_use]
+pub fn interior_points(vertices: &[Point<2>]) -> u64 {
+    let double_area = double_signed_area(vertices).unsigned_abs();
+    let boundary = u128::from(boundary_points(vertices));
+    // 2i = 2A - b + 2, which is never negative for a non-degenerate polygon
+    let double_interior = (double_area + 2).saturating_sub(boundary);
+    u64::try_from(double_interior / 2).expect("interior count fits in u64")
+}
+
+/// Whether the closed segments a1-a2 and b1-b2 share at least one point, touching ends included
+#[must_use]
+pub fn segments_intersect(a1: &Point<2>, a2: &Point<2>, b1: &Point<2>, b2: &Point<2>) -> bool {
+    let d1 = turn(b1, b2, a1);
+    let d2 = turn(b1, b2, a2);
+    let d3 = turn(a1, a2, b1);
+    let d4 = turn(a1, a2, b2);
+
+    let straddles = |x, y| {
+        matches!(
+            (x, y),
+            (Orientation::Clockwise, Orientation::CounterClockwise)
+                | (Orientation::CounterClockwise, Orientation::Clockwise)
+        )
+    };
+    if straddles(d1, d2) && straddles(d3, d4) {
+        return true;
+    }
+
+    (d1 == Orientation::Collinear && in_bounding_box(b1, b2, a1))
+        || (d2 == Orientation::Collinear && in_bounding_box(b1, b2, a2))
+        || (d3 == Orientation::Collinear && in_bounding_box(a1, a2, b1))
+        || (d4 == Orientation::Collinear && in_bounding_box(a1, a2, b2))
+}
+
+/// Cross product of (b - a) and (c - a)
+fn cross(a: &Point<2>, b: &Point<2>, c: &Point<2>) -> i128 {
+    let [ax, ay, bx, by, cx, cy] = [a.x(), a.y(), b.x(), b.y(), c.x(), c.y()].map(i128::from);
+    let (abx, aby) = (bx - ax, by - ay);
+    let (acx, acy) = (cx - ax, cy - ay);
+    abx * acy - aby * acx
+}
+
+/// Whether `p` is within the bounding box of the segment s1-s2
+fn in_bounding_box(s1: &Point<2>, s2: &Point<2>, p: &Point<2>) -> bool {
+    (s1.x().min(s2.x())..=s1.x().max(s2.x())).contains(&p.x())
+        && (s1.y().min(s2.y())..=s1.y().max(s2.y())).contains(&p.y())
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn points(coords: &[(i64, i64)]) -> Vec<Point<2>> {
+        coords.iter().map(|&(x, y)| Point::new([x, y])).collect()
+    }
+
+    #[test]
+    fn test_area() {
+        let square = points(&[(0, 0), (4, 0), (4, 4), (0, 4)]);
+        assert_eq!(32, double_signed_area(&square));
+        assert_eq!(16.0, area(&square));
+        assert_eq!(Orientation::CounterClockwise, orientation(&square));
+
+        let reversed: Vec<_> = square.iter().rev().copied().collect();
+        assert_eq!(-32, double_signed_area(&reversed));
+        assert_eq!(Orientation::Clockwise, orientation(&reversed));
+
+        let triangle = points(&[(0, 0), (3, 0), (0, 1)]);
+        assert_eq!(1.5, area(&triangle));
+    }
+
+    #[test]
+    fn test_boundary_and_interior() {
+        // The example from 2025 day 9, a rectilinear polygon
+        let polygon = points(&[
+            (7, 1),
+            (11, 1),
+            (11, 7),
+            (9, 7),
+            (9, 5),
+            (2, 5),
+            (2, 3),
+            (7, 3),
+        ]);
+        assert_eq!(30, boundary_points(&polygon));
+        assert_eq!(30.0, perimeter(&polygon));
+        assert_eq!(16, interior_points(&polygon));
+        // Every tile enclosed by the loop, counting the loop itself
+        assert_eq!(46, interior_points(&polygon) + boundary_points(&polygon));
+
+        let triangle = points(&[(0, 0), (4, 0), (0, 4)]);
+        assert_eq!(12, boundary_points(&triangle));
+        assert_eq!(3, interior_points(&triangle));
+    }
+
+    #[test]
+    fn test_segments_intersect() {
+        let p = |x, y| Point::new([x, y]);
+        // Crossing
+        assert!(segments_intersect(&p(0, 0), &p(4, 4), &p(0, 4), &p(4, 0)));
+        // Touching at an end
+        assert!(segments_intersect(&p(0, 0), &p(2, 2), &p(2, 2), &p(4, 0)));
+        // Collinear and overlapping
+        assert!(segments_intersect(&p(0, 0), &p(4, 0), &p(2, 0), &p(6, 0)));
+        // Collinear but apart
+        assert!(!segments_intersect(&p(0, 0), &p(1, 0), &p(2, 0), &p(6, 0)));
+        // Parallel
+        assert!(!segments_intersect(&p(0, 0), &p(4, 0), &p(0, 1), &p(4, 1)));
+        // Would cross if extended
+        assert!(!segments_intersect(&p(0, 0), &p(1, 1), &p(0, 4), &p(4, 0)));
+    }
+
+    #[test]
+    fn test_turn() {
+        let p = |x, y| Point::new([x, y]);
+        assert_eq!(
+            Orientation::CounterClockwise,
+            turn(&p(0, 0), &p(1, 0), &p(1, 1))
+        );
+        assert_eq!(Orientation::Clockwise, turn(&p(0, 0), &p(1, 0), &p(1, -1)));
+        assert_eq!(Orientation::Collinear, turn(&p(0, 0), &p(1, 0), &p(5, 0)));
+        // Far enough apart that the differences don't fit in an i64
+        assert_eq!(
+            Orientation::CounterClockwise,
+            turn(&p(i64::MIN, 0), &p(i64::MAX, 0), &p(0, 1))
+        );
+    }
+}
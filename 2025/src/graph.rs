@@ -0,0 +1,4 @@
+pub use adv_code_common::graph::*;
+
+pub mod dag;
+pub mod union_find;
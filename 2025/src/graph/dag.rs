@@ -0,0 +1,281 @@
+use crate::graph::{Direction, Graph};
+use anyhow::{Result, anyhow};
+use std::collections::VecDeque;
+
+/// Node ids ordered so every edge goes from an earlier node to a later one (Kahn's algorithm).
+/// Fails with an error naming the nodes on cycles if there are any.
+pub fn topological_order<W>(graph: &Graph<W>) -> Result<Vec<usize>> {
+    if graph.direction() == Direction::Undirected {
+        return Err(anyhow!("undirected graphs have no topological order"));
+    }
+    order_within(graph, &vec![true; graph.num_nodes()], |_| true)
+}
+
+/// Kahn's algorithm over the nodes marked in `within`, following the edges out of a node only if
+/// `expands` says so
+fn order_within<W, F>(graph: &Graph<W>, within: &[bool], expands: F) -> Result<Vec<usize>>
+where
+    F: Fn(usize) -> bool,
+{
+    let included = |id: usize| within.get(id).copied().unwrap_or(false);
+    let expands = &expands;
+    let followed = |from: usize| {
+        graph
+            .neighbors(from)
+            .map(|(to, _)| to)
+            .filter(move |&to| included(from) && expands(from) && included(to))
+    };
+
+    let mut in_degree = vec![0_usize; graph.num_nodes()];
+    for from in graph.node_ids() {
+        for to in followed(from) {
+            if let Some(d) = in_degree.get_mut(to) {
+                *d += 1;
+            }
+        }
+    }
+
+    let mut queue: VecDeque<usize> = graph
+        .node_ids()
+        .filter(|&id| included(id) && in_degree.get(id) == Some(&0))
+        .collect();
+    let mut order = Vec::with_capacity(graph.num_nodes());
+    while let Some(node) = queue.pop_front() {
+        order.push(node);
+        for next in followed(node) {
+            let Some(d) = in_degree.get_mut(next) else {
+                continue;
+            };
+            *d -= 1;
+            if *d == 0 {
+                queue.push_back(next);
+            }
+        }
+    }
+
+    // Nodes left with incoming edges are on a cycle or downstream of one. Peeling off the ones
+    // leading nowhere else that's left, the same way but backwards, leaves the cycles and anything
+    // caught between them.
+    let mut left: Vec<bool> = in_degree.iter().map(|&d| d > 0).collect();
+    let mut out_degree = vec![0_usize; graph.num_nodes()];
+    let mut predecessors = vec![Vec::new(); graph.num_nodes()];
+    for from in graph.node_ids().filter(|&id| in_degree.get(id) > Some(&0)) {
+        for to in followed(from).filter(|&to| in_degree.get(to) > Some(&0)) {
+            if let (Some(d), Some(preds)) = (out_degree.get_mut(from), predecessors.get_mut(to)) {
+                *d += 1;
+                preds.push(from);
+            }
+        }
+    }
+    let mut dead_ends: Vec<usize> = graph
+        .node_ids()
+        .filter(|&id| left.get(id) == Some(&true) && out_degree.get(id) == Some(&0))
+        .collect();
+    while let Some(node) = dead_ends.pop() {
+        if let Some(l) = left.get_mut(node) {
+            *l = false;
+        }
+        for &prev in predecessors.get(node).into_iter().flatten() {
+            let Some(d) = out_degree.get_mut(prev) else {
+                continue;
+            };
+            *d -= 1;
+            if *d == 0 {
+                dead_ends.push(prev);
+            }
+        }
+    }
+    let mut on_cycles: Vec<&str> = graph
+        .node_ids()
+        .filter(|&id| left.get(id) == Some(&true))
+        .filter_map(|id| graph.name(id))
+        .collect();
+    if !on_cycles.is_empty() {
+        on_cycles.sort_unstable();
+        return Err(anyhow!(
+            "graph has cycles through [{}]",
+            on_cycles.join(", ")
+        ));
+    }
+    Ok(order)
+}
+
+/// Most required nodes [`count_paths`] accepts. Every node keeps a `u128` count per subset of the
+/// required nodes, so at the cap that's `16 * 2^12` bytes, 64 KiB, per node.
+pub const MAX_REQUIRED: usize = 12;
+
+/// Number of paths from `source` to `sink` that pass through every node in `required`, in any
+/// order, and through none of the nodes in `forbidden`. Required nodes are tracked as a bitmask,
+/// so there can be at most [`MAX_REQUIRED`] of them.
+///
+/// Fails if there's a cycle reachable from `source` without going through `sink` or a forbidden
+/// node, there are too many required nodes, or the count overflows.
+pub fn count_paths<W>(
+    graph: &Graph<W>,
+    source: usize,
+    sink: usize,
+    required: &[usize],
+    forbidden: &[usize],
+) -> Result<u128> {
+    if graph.direction() == Direction::Undirected {
+        return Err(anyhow!("undirected graphs have no topological order"));
+    }
+    if required.len() > MAX_REQUIRED {
+        return Err(anyhow!("too many required nodes: {}", required.len()));
+    }
+    let num_masks = 1_usize << required.len();
+    let full = num_masks - 1;
+    if forbidden.contains(&source) || forbidden.contains(&sink) {
+        return Ok(0);
+    }
+
+    // Only what a path can actually reach has to be free of cycles
+    let mut reachable = vec![false; graph.num_nodes()];
+    let mut stack = vec![source];
+    while let Some(node) = stack.pop() {
+        match reachable.get_mut(node) {
+            Some(seen) if !*seen => *seen = true,
+            _ => continue,
+        }
+        if node != sink {
+            stack.extend(
+                graph
+                    .neighbors(node)
+                    .map(|(next, _)| next)
+                    .filter(|next| !forbidden.contains(next)),
+            );
+        }
+    }
+    let order = order_within(graph, &reachable, |node| node != sink)?;
+
+    let bit = |id: usize| {
+        required
+            .iter()
+            .enumerate()
+            .filter(|&(_, &r)| r == id)
+            .fold(0, |mask, (i, _)| mask | (1 << i))
+    };
+    // ways[node][mask]: paths from the source to node that have seen the required nodes in mask
+    let mut ways = vec![vec![0_u128; num_masks]; graph.num_nodes()];
+    *ways
+        .get_mut(source)
+        .and_then(|w| w.get_mut(bit(source)))
+        .ok_or_else(|| anyhow!("source {source} is not in the graph"))? = 1;
+
+    for node in order {
+        if node == sink {
+            continue;
+        }
+        let Some(counts) = ways.get(node).cloned() else {
+            continue;
+        };
+        for (next, _) in graph.neighbors(node) {
+            if forbidden.contains(&next) {
+                continue;
+            }
+            let next_bit = bit(next);
+            let Some(next_ways) = ways.get_mut(next) else {
+                continue;
+            };
+            for (mask, &count) in counts.iter().enumerate().filter(|&(_, &c)| c > 0) {
+                if let Some(slot) = next_ways.get_mut(mask | next_bit) {
+                    *slot = slot
+                        .checked_add(count)
+                        .ok_or_else(|| anyhow!("path count overflows u128"))?;
+                }
+            }
+        }
+    }
+
+    ways.get(sink)
+        .and_then(|w| w.get(full))
+        .copied()
+        .ok_or_else(|| anyhow!("sink {sink} is not in the graph"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::Storage;
+
+    fn diamond() -> Graph<()> {
+        // a -> b -> d, a -> c -> d, d -> e, b -> c
+        let mut g = Graph::new(Direction::Directed, Storage::AdjacencyList);
+        for (from, to) in [
+            ("a", "b"),
+            ("a", "c"),
+            ("b", "d"),
+            ("c", "d"),
+            ("d", "e"),
+            ("b", "c"),
+        ] {
+            g.add_edge(from, to, ());
+        }
+        g
+    }
+
+    #[test]
+    fn test_topological_order() {
+        let g = diamond();
+        let order = topological_order(&g).expect("acyclic");
+        let pos = |name| order.iter().position(|&id| Some(id) == g.id(name));
+        for (from, to, _) in g.edges() {
+            let (from, to) = (g.name(from).expect("a"), g.name(to).expect("b"));
+            assert!(pos(from) < pos(to), "{from} before {to}");
+        }
+    }
+
+    #[test]
+    fn test_cycle_error() {
+        let mut g = diamond();
+        g.add_edge("d", "b", ());
+        let err = topological_order(&g).expect_err("cyclic");
+        assert_eq!("graph has cycles through [b, c, d]", err.to_string());
+        assert!(count_paths(&g, 0, 4, &[], &[]).is_err());
+
+        // Nodes after a cycle aren't named, unless they're on one too
+        g.add_edge("d", "f", ());
+        g.add_edge("f", "f", ());
+        let err = topological_order(&g).expect_err("cyclic");
+        assert_eq!("graph has cycles through [b, c, d, f]", err.to_string());
+    }
+
+    #[test]
+    fn test_unreachable_cycle() {
+        let mut g = diamond();
+        g.add_edge("x", "y", ());
+        g.add_edge("y", "x", ());
+        g.add_edge("y", "b", ());
+        g.add_edge("e", "z", ());
+        g.add_edge("z", "e", ());
+        let id = |name| g.id(name).expect("exists");
+        assert!(topological_order(&g).is_err());
+        // Neither the cycle leading in nor the one past the sink is on a path
+        assert_eq!(
+            3,
+            count_paths(&g, id("a"), id("e"), &[], &[]).expect("reachable part acyclic")
+        );
+        assert!(count_paths(&g, id("x"), id("e"), &[], &[]).is_err());
+        // Forbidding y cuts x off from everything, cycle included
+        assert_eq!(
+            0,
+            count_paths(&g, id("x"), id("e"), &[], &[id("y")]).expect("nothing reachable")
+        );
+    }
+
+    #[test]
+    fn test_count_paths() {
+        let g = diamond();
+        let id = |name| g.id(name).expect("exists");
+        let (a, b, c, d, e) = (id("a"), id("b"), id("c"), id("d"), id("e"));
+        assert_eq!(3, count_paths(&g, a, e, &[], &[]).expect("acyclic"));
+        assert_eq!(2, count_paths(&g, a, e, &[b], &[]).expect("acyclic"));
+        assert_eq!(1, count_paths(&g, a, e, &[b, c], &[]).expect("acyclic"));
+        assert_eq!(1, count_paths(&g, a, e, &[], &[b]).expect("acyclic"));
+        assert_eq!(0, count_paths(&g, a, e, &[c], &[d]).expect("acyclic"));
+        assert_eq!(0, count_paths(&g, e, a, &[], &[]).expect("acyclic"));
+
+        let too_many = vec![a; MAX_REQUIRED + 1];
+        assert!(count_paths(&g, a, e, &too_many, &[]).is_err());
+    }
+}
@@ -0,0 +1,232 @@
+use std::collections::HashMap;
+
+/// Union-find over the elements `0..len`, with union by size and path compression
+#[derive(Debug, Clone)]
+pub struct DisjointSets {
+    parents: Vec<usize>,
+    sizes: Vec<usize>,
+    num_components: usize,
+}
+
+// See: https://en.wikipedia.org/wiki/Disjoint-set_data_structure#Operations
+impl DisjointSets {
+    /// Each of the elements `0..len` in a set of its own
+    #[must_use]
+    pub fn new(len: usize) -> Self {
+        DisjointSets {
+            parents: (0..len).collect(),
+            sizes: vec![1; len],
+            num_components: len,
+        }
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.parents.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.parents.is_empty()
+    }
+
+    /// Representative of the set containing `x`, or `None` if `x` is out of range
+    pub fn find(&mut self, x: usize) -> Option<usize> {
+        let mut root = x;
+        loop {
+            let parent = *self.parents.get(root)?;
+            if parent == root {
+                break;
+            }
+            root = parent;
+        }
+
+        // Second pass points everything on the path straight at the root
+        let mut curr = x;
+        while curr != root {
+            let parent = self.parents.get_mut(curr).expect("on the path to the root");
+            curr = std::mem::replace(parent, root);
+        }
+        Some(root)
+    }
+
+    /// Merge the sets containing `a` and `b`. Returns whether they were separate before, or
+    /// `None` if either is out of range.
+    pub fn union(&mut self, a: usize, b: usize) -> Option<bool> {
+        let (a_root, b_root) = (self.find(a)?, self.find(b)?);
+        if a_root == b_root {
+            return Some(false);
+        }
+
+        let (a_size, b_size) = (self.size_of_root(a_root), self.size_of_root(b_root));
+        let (smaller, larger) = if a_size < b_size {
+            (a_root, b_root)
+        } else {
+            (b_root, a_root)
+        };
+        if let Some(parent) = self.parents.get_mut(smaller) {
+            *parent = larger;
+        }
+        if let Some(size) = self.sizes.get_mut(larger) {
+            *size = a_size + b_size;
+        }
+        self.num_components -= 1;
+        Some(true)
+    }
+
+    /// Whether `a` and `b` are in the same set
+    pub fn connected(&mut self, a: usize, b: usize) -> bool {
+        matches!((self.find(a), self.find(b)), (Some(a), Some(b)) if a == b)
+    }
+
+    /// Size of the set containing `x`
+    pub fn size_of(&mut self, x: usize) -> Option<usize> {
+        let root = self.find(x)?;
+        Some(self.size_of_root(root))
+    }
+
+    #[must_use]
+    pub fn num_components(&self) -> usize {
+        self.num_components
+    }
+
+    /// Size of every set, in no particular order
+    pub fn component_sizes(&self) -> impl Iterator<Item = usize> + '_ {
+        self.parents
+            .iter()
+            .zip(&self.sizes)
+            .enumerate()
+            .filter(|&(x, (&parent, _))| parent == x)
+            .map(|(_, (_, &size))| size)
+    }
+
+    /// Members of every set keyed by the set's representative, each list in increasing order
+    pub fn components(&mut self) -> HashMap<usize, Vec<usize>> {
+        let mut result: HashMap<usize, Vec<usize>> = HashMap::new();
+        for x in 0..self.len() {
+            let root = self.find(x).expect("x is in range");
+            result.entry(root).or_default().push(x);
+        }
+        result
+    }
+
+    fn size_of_root(&self, root: usize) -> usize {
+        self.sizes.get(root).copied().unwrap_or_default()
+    }
+}
+
+/// Result of running Kruskal's algorithm
+#[derive(Debug, Clone)]
+pub struct SpanningForest<W> {
+    edges: Vec<(usize, usize, W)>,
+    sets: DisjointSets,
+}
+
+impl<W> SpanningForest<W> {
+    /// Edges of the forest in the order they were added, i.e. by increasing weight
+    #[must_use]
+    pub fn edges(&self) -> &[(usize, usize, W)] {
+        &self.edges
+    }
+
+    /// Edge whose addition joined everything into a single tree, or `None` if the graph isn't
+    /// connected (or has fewer than two nodes)
+    #[must_use]
+    pub fn connecting_edge(&self) -> Option<&(usize, usize, W)> {
+        if self.sets.num_components() == 1 {
+            self.edges.last()
+        } else {
+            None
+        }
+    }
+
+    /// The sets of nodes joined by the forest, one per tree
+    #[must_use]
+    pub fn sets(&self) -> &DisjointSets {
+        &self.sets
+    }
+}
+
+/// Minimum spanning forest of the nodes `0..num_nodes` with the given weighted edges
+pub fn kruskal<W, I>(num_nodes: usize, edges: I) -> SpanningForest<W>
+where
+    W: Ord,
+    I: IntoIterator<Item = (usize, usize, W)>,
+{
+    let mut edges: Vec<_> = edges.into_iter().collect();
+    edges.sort_by(|a, b| a.2.cmp(&b.2));
+    kruskal_sorted(num_nodes, edges)
+}
+
+/// Kruskal's algorithm for edges already in increasing order of weight. Stops pulling edges as
+/// soon as everything is connected, so `edges` can be a lazy, even endless, iterator. Edges
+/// naming nodes out of range are skipped.
+pub fn kruskal_sorted<W, I>(num_nodes: usize, edges: I) -> SpanningForest<W>
+where
+    I: IntoIterator<Item = (usize, usize, W)>,
+{
+    let mut sets = DisjointSets::new(num_nodes);
+    let mut forest = Vec::with_capacity(num_nodes.saturating_sub(1));
+    for (a, b, w) in edges {
+        if sets.num_components() <= 1 {
+            break;
+        }
+        if sets.union(a, b) == Some(true) {
+            forest.push((a, b, w));
+        }
+    }
+    SpanningForest {
+        edges: forest,
+        sets,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disjoint_sets() {
+        let mut sets = DisjointSets::new(6);
+        assert_eq!(6, sets.num_components());
+        assert_eq!(Some(true), sets.union(0, 1));
+        assert_eq!(Some(true), sets.union(2, 3));
+        assert_eq!(Some(true), sets.union(1, 3));
+        assert_eq!(Some(false), sets.union(0, 2));
+        assert_eq!(None, sets.union(0, 6));
+
+        assert!(sets.connected(0, 3));
+        assert!(!sets.connected(0, 4));
+        assert_eq!(Some(4), sets.size_of(2));
+        assert_eq!(3, sets.num_components());
+
+        let mut sizes: Vec<_> = sets.component_sizes().collect();
+        sizes.sort_unstable();
+        assert_eq!(vec![1, 1, 4], sizes);
+
+        let root = sets.find(0).expect("in range");
+        let components = sets.components();
+        assert_eq!(3, components.len());
+        assert_eq!(Some(&vec![0, 1, 2, 3]), components.get(&root));
+    }
+
+    #[test]
+    fn test_kruskal() {
+        let edges = [(0, 1, 4), (1, 2, 1), (0, 2, 2), (2, 3, 7), (1, 3, 5)];
+        let forest = kruskal(4, edges);
+        assert_eq!(&[(1, 2, 1), (0, 2, 2), (1, 3, 5)], forest.edges());
+        assert_eq!(Some(&(1, 3, 5)), forest.connecting_edge());
+
+        let disconnected = kruskal(5, edges);
+        assert_eq!(3, disconnected.edges().len());
+        assert_eq!(None, disconnected.connecting_edge());
+        assert_eq!(2, disconnected.sets().num_components());
+    }
+
+    #[test]
+    fn test_kruskal_sorted_stops_early() {
+        let endless = (0..).map(|i| (i % 3, (i + 1) % 3, i));
+        let forest = kruskal_sorted(3, endless);
+        assert_eq!(Some(&(1, 2, 1)), forest.connecting_edge());
+    }
+}
@@ -1,6 +1,16 @@
 use anyhow::{Result, anyhow};
+use itertools::Either;
 
-#[derive(Debug, PartialEq, Eq)]
+pub mod hex;
+pub mod kd_tree;
+pub mod packing;
+pub mod paths;
+pub mod point;
+pub mod regions;
+pub mod sparse;
+pub mod transforms;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Position {
     row: usize,
     col: usize,
@@ -19,10 +29,61 @@ impl Position {
         Position { row, col }
     }
 
+    #[must_use]
+    pub fn row(&self) -> usize {
+        self.row
+    }
+
+    #[must_use]
+    pub fn col(&self) -> usize {
+        self.col
+    }
+
     #[must_use]
     pub fn adjacent<'a>(&'a self) -> AdjacentIter<'a> {
         AdjacentIter::new(self)
     }
+
+    /// The up to four positions sharing an edge with this one
+    pub fn orthogonal(&self) -> impl Iterator<Item = Position> + '_ {
+        [(-1, 0), (0, -1), (0, 1), (1, 0)]
+            .into_iter()
+            .filter_map(|(row_offset, col_offset)| {
+                let row = self.row.checked_add_signed(row_offset)?;
+                let col = self.col.checked_add_signed(col_offset)?;
+                Some(Position::new(row, col))
+            })
+    }
+
+    #[must_use]
+    pub fn manhattan_distance(&self, other: &Position) -> usize {
+        self.row.abs_diff(other.row) + self.col.abs_diff(other.col)
+    }
+
+    #[must_use]
+    pub fn chebyshev_distance(&self, other: &Position) -> usize {
+        self.row
+            .abs_diff(other.row)
+            .max(self.col.abs_diff(other.col))
+    }
+}
+
+/// Which positions count as neighbors when walking the grid
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Connectivity {
+    /// Up, down, left and right
+    Four,
+    /// Diagonals too
+    Eight,
+}
+
+impl Connectivity {
+    pub fn neighbors(self, p: &Position) -> impl Iterator<Item = Position> + '_ {
+        match self {
+            Connectivity::Four => Either::Left(p.orthogonal()),
+            Connectivity::Eight => Either::Right(p.adjacent()),
+        }
+    }
 }
 
 impl<'a> AdjacentIter<'a> {
@@ -63,6 +124,7 @@ impl<'a> Iterator for AdjacentIter<'a> {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Grid<T>(Vec<Vec<T>>);
 
 impl<T> Grid<T> {
@@ -92,6 +154,21 @@ impl<T> Grid<T> {
     pub fn all_positions(&self) -> PositionsIter<'_, T> {
         PositionsIter::new(self)
     }
+
+    pub fn rows(&self) -> impl DoubleEndedIterator<Item = &[T]> {
+        self.0.iter().map(Vec::as_slice)
+    }
+
+    #[must_use]
+    pub fn num_rows(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Length of the longest row
+    #[must_use]
+    pub fn num_cols(&self) -> usize {
+        self.0.iter().map(Vec::len).max().unwrap_or(0)
+    }
 }
 
 pub struct PositionsIter<'a, T> {
@@ -191,4 +268,21 @@ mod tests {
                 .collect::<Vec<_>>()
         )
     }
+
+    #[test]
+    fn test_orthogonal() {
+        assert_eq!(
+            vec![Position::new(0, 1), Position::new(1, 0)],
+            Position::new(0, 0).orthogonal().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![
+                Position::new(0, 1),
+                Position::new(1, 0),
+                Position::new(1, 2),
+                Position::new(2, 1)
+            ],
+            Position::new(1, 1).orthogonal().collect::<Vec<_>>()
+        );
+    }
 }
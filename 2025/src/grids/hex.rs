@@ -0,0 +1,130 @@
+use crate::grids::sparse::Neighbors;
+use anyhow::{Result, anyhow};
+use std::ops::{Add, Sub};
+
+/// Position on a hexagonal grid in axial coordinates.
+///
+/// The third cube coordinate `s` is implied by `q + r + s = 0`. Moving along one of the six
+/// directions changes two of the three cube coordinates by one in opposite directions, which
+/// works the same whether the hexes are drawn pointy-top or flat-top.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct HexCoord {
+    q: i64,
+    r: i64,
+}
+
+/// Axial (q, r) offsets to the six neighbors, going around the hex
+const DIRECTIONS: [(i64, i64); 6] = [(1, 0), (1, -1), (0, -1), (-1, 0), (-1, 1), (0, 1)];
+
+impl HexCoord {
+    #[must_use]
+    pub fn new(q: i64, r: i64) -> Self {
+        HexCoord { q, r }
+    }
+
+    /// Build from cube coordinates, which must sum to zero
+    pub fn from_cube(q: i64, r: i64, s: i64) -> Result<Self> {
+        if q + r + s != 0 {
+            return Err(anyhow!(
+                "cube coordinates ({q}, {r}, {s}) don't sum to zero"
+            ));
+        }
+        Ok(HexCoord { q, r })
+    }
+
+    #[must_use]
+    pub fn q(&self) -> i64 {
+        self.q
+    }
+
+    #[must_use]
+    pub fn r(&self) -> i64 {
+        self.r
+    }
+
+    #[must_use]
+    pub fn s(&self) -> i64 {
+        -self.q - self.r
+    }
+
+    /// Neighbor in direction `dir`, counting counter-clockwise from +q. Wraps around after six.
+    #[must_use]
+    pub fn neighbor(&self, dir: usize) -> Self {
+        self.adjacent()
+            .nth(dir % DIRECTIONS.len())
+            .expect("index is within the six directions")
+    }
+
+    /// The six hexes sharing an edge with this one
+    pub fn adjacent(&self) -> impl Iterator<Item = Self> + '_ {
+        DIRECTIONS
+            .iter()
+            .map(|(dq, dr)| HexCoord::new(self.q + dq, self.r + dr))
+    }
+
+    /// Number of steps between two hexes. This is the Chebyshev distance between the cube
+    /// coordinates, or equivalently half their Manhattan distance.
+    #[must_use]
+    pub fn distance(&self, other: &Self) -> u64 {
+        self.q
+            .abs_diff(other.q)
+            .max(self.r.abs_diff(other.r))
+            .max(self.s().abs_diff(other.s()))
+    }
+}
+
+impl Add for HexCoord {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        HexCoord::new(self.q + rhs.q, self.r + rhs.r)
+    }
+}
+
+impl Sub for HexCoord {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        HexCoord::new(self.q - rhs.q, self.r - rhs.r)
+    }
+}
+
+impl Neighbors for HexCoord {
+    fn neighbors(&self) -> impl Iterator<Item = Self> {
+        self.adjacent()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_cube() {
+        assert_eq!(
+            HexCoord::new(1, -3),
+            HexCoord::from_cube(1, -3, 2).expect("valid")
+        );
+        assert!(HexCoord::from_cube(1, 1, 1).is_err());
+    }
+
+    #[test]
+    fn test_neighbors() {
+        let origin = HexCoord::new(0, 0);
+        assert_eq!(6, origin.adjacent().count());
+        assert!(origin.adjacent().all(|n| origin.distance(&n) == 1));
+        assert_eq!(origin.neighbor(0), origin.neighbor(6));
+        assert_eq!(origin, origin.neighbor(1).neighbor(4));
+    }
+
+    #[test]
+    fn test_distance() {
+        let a = HexCoord::new(0, 0);
+        let b = HexCoord::new(3, -1);
+        assert_eq!(3, a.distance(&b));
+        assert_eq!(3, b.distance(&a));
+        assert_eq!(5, a.distance(&HexCoord::new(-2, -3)));
+        assert_eq!(HexCoord::new(3, -1), a + b);
+        assert_eq!(HexCoord::new(-3, 1), a - b);
+    }
+}
@@ -0,0 +1,345 @@
+use crate::grids::point::Point;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::ops::Range;
+
+/// Number of neighbors [`PairsByDistance`] first looks up for each point, doubled whenever it
+/// runs out
+const INITIAL_NEIGHBORS: usize = 4;
+
+/// Static k-d tree over integer points for nearest-neighbor queries. Distances are squared
+/// Euclidean, and points are referred to by their index in the slice the tree was built from.
+///
+/// The tree is implicit: each range of `order` holds a subtree whose root is the middle element,
+/// split on axis `depth % N`, with the left half no greater along that axis and the right half no
+/// less.
+#[derive(Debug, Clone)]
+pub struct KdTree<const N: usize> {
+    points: Vec<Point<N>>,
+    order: Vec<usize>,
+}
+
+impl<const N: usize> KdTree<N> {
+    #[must_use]
+    pub fn new(points: &[Point<N>]) -> Self {
+        let mut tree = KdTree {
+            points: points.to_vec(),
+            order: (0..points.len()).collect(),
+        };
+        tree.build(0..points.len(), 0);
+        tree
+    }
+
+    fn build(&mut self, range: Range<usize>, depth: usize) {
+        if range.len() <= 1 {
+            return;
+        }
+        let mid = midpoint(&range);
+        let axis = depth % N;
+        let points = &self.points;
+        if let Some(slice) = self.order.get_mut(range.clone()) {
+            slice.select_nth_unstable_by_key(mid - range.start, |&i| coord(points, i, axis));
+        }
+        self.build(range.start..mid, depth + 1);
+        self.build(mid + 1..range.end, depth + 1);
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    #[must_use]
+    pub fn point(&self, idx: usize) -> Option<&Point<N>> {
+        self.points.get(idx)
+    }
+
+    /// The `k` points closest to `target` as `(index, squared distance)`, closest first. Ties are
+    /// broken by index.
+    #[must_use]
+    pub fn nearest(&self, target: &Point<N>, k: usize) -> Vec<(usize, u64)> {
+        let mut best = BinaryHeap::with_capacity(k + 1);
+        if k > 0 {
+            self.search_nearest(0..self.len(), 0, target, k, &mut best);
+        }
+        best.into_sorted_vec()
+            .into_iter()
+            .map(|(dist, idx)| (idx, dist))
+            .collect()
+    }
+
+    /// `best` is a max-heap of the closest `(distance, index)` found so far
+    fn search_nearest(
+        &self,
+        range: Range<usize>,
+        depth: usize,
+        target: &Point<N>,
+        k: usize,
+        best: &mut BinaryHeap<(u64, usize)>,
+    ) {
+        let Some((idx, near, far, plane)) = self.split(&range, depth, target) else {
+            return;
+        };
+        let dist = self.distance(idx, target);
+        best.push((dist, idx));
+        if best.len() > k {
+            best.pop();
+        }
+
+        self.search_nearest(near, depth + 1, target, k, best);
+        // Ties count, since a point at the same distance could still win on index
+        if best.len() < k || best.peek().is_some_and(|&(worst, _)| plane <= worst) {
+            self.search_nearest(far, depth + 1, target, k, best);
+        }
+    }
+
+    /// Every point within `sqrt(max_squared_distance)` of `target`, inclusive, as
+    /// `(index, squared distance)`, closest first
+    #[must_use]
+    pub fn within(&self, target: &Point<N>, max_squared_distance: u64) -> Vec<(usize, u64)> {
+        let mut found = Vec::new();
+        self.search_within(0..self.len(), 0, target, max_squared_distance, &mut found);
+        found.sort_unstable_by_key(|&(idx, dist)| (dist, idx));
+        found
+    }
+
+    fn search_within(
+        &self,
+        range: Range<usize>,
+        depth: usize,
+        target: &Point<N>,
+        max_dist: u64,
+        found: &mut Vec<(usize, u64)>,
+    ) {
+        let Some((idx, near, far, plane)) = self.split(&range, depth, target) else {
+            return;
+        };
+        let dist = self.distance(idx, target);
+        if dist <= max_dist {
+            found.push((idx, dist));
+        }
+        self.search_within(near, depth + 1, target, max_dist, found);
+        if plane <= max_dist {
+            self.search_within(far, depth + 1, target, max_dist, found);
+        }
+    }
+
+    /// Every pair of distinct points as `(index, index, squared distance)` with the smaller
+    /// index first, in increasing order of distance and then of indices. Pairs are found as
+    /// they're needed rather than all up front, so taking the closest few is cheap.
+    #[must_use]
+    pub fn pairs_by_distance(&self) -> PairsByDistance<'_, N> {
+        PairsByDistance::new(self)
+    }
+
+    /// Root of the subtree in `range`, the child subtrees nearer to and further from `target`,
+    /// and the squared distance from `target` to the splitting plane
+    fn split(
+        &self,
+        range: &Range<usize>,
+        depth: usize,
+        target: &Point<N>,
+    ) -> Option<(usize, Range<usize>, Range<usize>, u64)> {
+        if range.is_empty() {
+            return None;
+        }
+        let mid = midpoint(range);
+        let idx = *self.order.get(mid)?;
+        let axis = depth % N;
+        let diff = target.get(axis)? - coord(&self.points, idx, axis);
+        let (left, right) = (range.start..mid, mid + 1..range.end);
+        let (near, far) = if diff < 0 {
+            (left, right)
+        } else {
+            (right, left)
+        };
+        Some((idx, near, far, diff.unsigned_abs().pow(2)))
+    }
+
+    fn distance(&self, idx: usize, target: &Point<N>) -> u64 {
+        self.points
+            .get(idx)
+            .map_or(u64::MAX, |p| p.squared_euclidean_distance(target))
+    }
+}
+
+fn midpoint(range: &Range<usize>) -> usize {
+    range.start + range.len() / 2
+}
+
+fn coord<const N: usize>(points: &[Point<N>], idx: usize, axis: usize) -> i64 {
+    points
+        .get(idx)
+        .and_then(|p| p.get(axis))
+        .expect("indices and axes are in range by construction")
+}
+
+/// Iterator returned by [`KdTree::pairs_by_distance`].
+///
+/// Each point keeps a buffer of its nearest neighbors, and a heap holds the next unused neighbor
+/// of every point. Each pair turns up once from either end, and is only yielded from the end with
+/// the smaller index.
+pub struct PairsByDistance<'a, const N: usize> {
+    tree: &'a KdTree<N>,
+    neighbors: Vec<Vec<(usize, u64)>>,
+    next: Vec<usize>,
+    heap: BinaryHeap<Reverse<(u64, usize, usize)>>,
+}
+
+impl<'a, const N: usize> PairsByDistance<'a, N> {
+    fn new(tree: &'a KdTree<N>) -> Self {
+        let mut pairs = PairsByDistance {
+            tree,
+            neighbors: Vec::with_capacity(tree.len()),
+            next: vec![0; tree.len()],
+            heap: BinaryHeap::with_capacity(tree.len()),
+        };
+        for idx in 0..tree.len() {
+            let neighbors = pairs.fetch(idx, INITIAL_NEIGHBORS);
+            if let Some(&(other, dist)) = neighbors.first() {
+                pairs.heap.push(Reverse((dist, idx, other)));
+            }
+            pairs.neighbors.push(neighbors);
+        }
+        pairs
+    }
+
+    /// The `k` nearest neighbors of point `idx`, leaving out the point itself
+    fn fetch(&self, idx: usize, k: usize) -> Vec<(usize, u64)> {
+        let Some(target) = self.tree.point(idx) else {
+            return Vec::new();
+        };
+        self.tree
+            .nearest(target, k + 1)
+            .into_iter()
+            .filter(|&(other, _)| other != idx)
+            .take(k)
+            .collect()
+    }
+
+    /// Move point `idx` on to its next neighbor, fetching more if needed
+    fn advance(&mut self, idx: usize) {
+        let Some(next) = self.next.get_mut(idx) else {
+            return;
+        };
+        *next += 1;
+        let next = *next;
+        let buffered = self.neighbors.get(idx).map_or(0, Vec::len);
+        if next >= buffered && buffered + 1 < self.tree.len() {
+            let more = self.fetch(idx, buffered * 2);
+            if let Some(neighbors) = self.neighbors.get_mut(idx) {
+                *neighbors = more;
+            }
+        }
+        if let Some(&(other, dist)) = self.neighbors.get(idx).and_then(|n| n.get(next)) {
+            self.heap.push(Reverse((dist, idx, other)));
+        }
+    }
+}
+
+impl<const N: usize> Iterator for PairsByDistance<'_, N> {
+    type Item = (usize, usize, u64);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let Reverse((dist, idx, other)) = self.heap.pop()?;
+            self.advance(idx);
+            if idx < other {
+                return Some((idx, other, dist));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use itertools::Itertools;
+
+    fn scattered() -> Vec<Point<3>> {
+        // Steps coprime to 101 visit a different spot along each axis every time
+        (0..100)
+            .map(|i| Point::new([i * 7 % 101, i * 19 % 101, i * 33 % 101]))
+            .collect()
+    }
+
+    fn brute_force_pairs(points: &[Point<3>]) -> Vec<(usize, usize, u64)> {
+        points
+            .iter()
+            .enumerate()
+            .tuple_combinations()
+            .map(|((i, a), (j, b))| (i, j, a.squared_euclidean_distance(b)))
+            .sorted_by_key(|&(i, j, dist)| (dist, i, j))
+            .collect()
+    }
+
+    #[test]
+    fn test_nearest() {
+        let points = scattered();
+        let tree = KdTree::new(&points);
+        let target = Point::new([50, 50, 50]);
+
+        let expected: Vec<_> = points
+            .iter()
+            .enumerate()
+            .map(|(i, p)| (i, p.squared_euclidean_distance(&target)))
+            .sorted_by_key(|&(i, dist)| (dist, i))
+            .take(7)
+            .collect();
+        assert_eq!(expected, tree.nearest(&target, 7));
+        assert!(tree.nearest(&target, 0).is_empty());
+        assert_eq!(100, tree.nearest(&target, 1000).len());
+    }
+
+    #[test]
+    fn test_within() {
+        let points = scattered();
+        let tree = KdTree::new(&points);
+        let target = Point::new([20, 80, 0]);
+
+        let expected: Vec<_> = points
+            .iter()
+            .enumerate()
+            .map(|(i, p)| (i, p.squared_euclidean_distance(&target)))
+            .filter(|&(_, dist)| dist <= 1600)
+            .sorted_by_key(|&(i, dist)| (dist, i))
+            .collect();
+        assert!(!expected.is_empty());
+        assert_eq!(expected, tree.within(&target, 1600));
+    }
+
+    #[test]
+    fn test_pairs_by_distance() {
+        let points = scattered();
+        let tree = KdTree::new(&points);
+        assert_eq!(
+            brute_force_pairs(&points),
+            tree.pairs_by_distance().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_two_dimensions_and_duplicates() {
+        let points = [
+            Point::new([0, 0]),
+            Point::new([3, 4]),
+            Point::new([0, 0]),
+            Point::new([1, 1]),
+        ];
+        let tree = KdTree::new(&points);
+        assert_eq!(vec![(0, 0), (2, 0)], tree.nearest(&Point::new([0, 0]), 2));
+        assert_eq!(
+            vec![(0, 2, 0), (0, 3, 2), (2, 3, 2)],
+            tree.pairs_by_distance().take(3).collect::<Vec<_>>()
+        );
+
+        let empty: KdTree<2> = KdTree::new(&[]);
+        assert!(empty.nearest(&Point::origin(), 3).is_empty());
+        assert_eq!(0, empty.pairs_by_distance().count());
+    }
+}
@@ -0,0 +1,521 @@
+use crate::grids::{Grid, Position};
+use anyhow::{Result, anyhow};
+use std::collections::HashSet;
+
+/// Widest region [`pack`] handles, as each row of the board is kept in a single word
+const MAX_WIDTH: usize = u128::BITS as usize;
+
+/// A piece made of grid cells, along with each of its distinct rotations and reflections
+#[derive(Debug, Clone)]
+pub struct Polyomino {
+    orientations: Vec<Orientation>,
+    area: usize,
+}
+
+/// One way round a piece, cropped to its bounding box
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Orientation {
+    /// Per row of the bounding box, bit `c` is set for a cell in column `c`
+    rows: Vec<u128>,
+    width: usize,
+    /// Column of the leftmost cell in the top row, the first cell met scanning row by row
+    anchor: usize,
+}
+
+impl Orientation {
+    fn new(cells: &Grid<bool>) -> Self {
+        let rows: Vec<u128> = cells
+            .rows()
+            .map(|row| {
+                row.iter()
+                    .enumerate()
+                    .filter(|&(_, &filled)| filled)
+                    .fold(0, |mask, (c, _)| mask | 1 << c)
+            })
+            .collect();
+        let anchor = rows.first().map_or(0, |r| r.trailing_zeros() as usize);
+        Orientation {
+            rows,
+            width: cells.num_cols(),
+            anchor,
+        }
+    }
+
+    fn height(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Cells covered with the top left of the bounding box at `(row, col)`
+    fn cells(&self, row: usize, col: usize) -> impl Iterator<Item = Position> + '_ {
+        self.rows.iter().enumerate().flat_map(move |(dr, &mask)| {
+            (0..self.width)
+                .filter(move |dc| mask & (1 << dc) != 0)
+                .map(move |dc| Position::new(row + dr, col + dc))
+        })
+    }
+}
+
+impl Polyomino {
+    /// The piece with a cell wherever `shape` is true. Empty rows and columns around it are
+    /// ignored.
+    /// # Errors
+    /// If there are no cells, or the piece is wider or taller than 128
+    pub fn new(shape: &Grid<bool>) -> Result<Self> {
+        let filled: Vec<_> = shape
+            .all_positions()
+            .filter(|p| shape.get(p) == Some(&true))
+            .collect();
+        let rows = filled.iter().map(Position::row);
+        let cols = filled.iter().map(Position::col);
+        let (Some(top), Some(bottom), Some(left), Some(right)) = (
+            rows.clone().min(),
+            rows.max(),
+            cols.clone().min(),
+            cols.max(),
+        ) else {
+            return Err(anyhow!("a piece needs at least one cell"));
+        };
+        let (height, width) = (bottom - top + 1, right - left + 1);
+        if height.max(width) > MAX_WIDTH {
+            return Err(anyhow!("piece of {height}x{width} is too big"));
+        }
+
+        let cropped = Grid::from_jagged(shape.rows().map(<[bool]>::to_vec).collect(), false).crop(
+            &Position::new(top, left),
+            height,
+            width,
+        )?;
+        Ok(Polyomino {
+            orientations: cropped
+                .orientations()
+                .iter()
+                .map(Orientation::new)
+                .collect(),
+            area: filled.len(),
+        })
+    }
+
+    /// Number of cells
+    #[must_use]
+    pub fn area(&self) -> usize {
+        self.area
+    }
+
+    /// Number of distinct rotations and reflections, between one and eight
+    #[must_use]
+    pub fn num_orientations(&self) -> usize {
+        self.orientations.len()
+    }
+
+    /// Longest side of the bounding box, the same whichever way round
+    fn extent(&self) -> usize {
+        self.orientations
+            .first()
+            .map_or(0, |o| o.height().max(o.width))
+    }
+}
+
+/// Where one piece went in a [`Packing`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Placement {
+    piece: usize,
+    cells: Vec<Position>,
+}
+
+impl Placement {
+    /// Index of the piece in the list given to [`pack`]
+    #[must_use]
+    pub fn piece(&self) -> usize {
+        self.piece
+    }
+
+    #[must_use]
+    pub fn cells(&self) -> &[Position] {
+        &self.cells
+    }
+}
+
+/// A way of fitting every piece into a region, as found by [`pack`]
+#[derive(Debug, Clone)]
+pub struct Packing {
+    num_rows: usize,
+    num_cols: usize,
+    placements: Vec<Placement>,
+}
+
+impl Packing {
+    #[must_use]
+    pub fn placements(&self) -> &[Placement] {
+        &self.placements
+    }
+
+    /// The region with each cell holding the index of the placement covering it
+    #[must_use]
+    pub fn to_grid(&self) -> Grid<Option<usize>> {
+        let mut grid = Grid::new(vec![vec![None; self.num_cols]; self.num_rows]);
+        for (i, placement) in self.placements.iter().enumerate() {
+            for p in &placement.cells {
+                grid.set(p, Some(i))
+                    .expect("placements are inside the region");
+            }
+        }
+        grid
+    }
+
+    /// The region drawn a line per row, with a letter per placement (going round the alphabet
+    /// again after Z) and `.` for empty cells
+    #[must_use]
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        for row in self.to_grid().rows() {
+            out.extend(row.iter().map(|cell| match cell {
+                Some(i) => char::from(b'A' + u8::try_from(i % 26).expect("below 26")),
+                None => '.',
+            }));
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// Fit `counts[i]` copies of `pieces[i]` into a `num_rows` x `num_cols` region without any
+/// overlapping, turning and flipping them as needed. Returns where each piece went, or `None` if
+/// they can't all fit.
+///
+/// When there's room to give every piece a square box of its own the answer is immediate.
+/// Otherwise this backtracks, filling the first empty cell each time either with a piece or, if
+/// the total area leaves enough to spare, with nothing.
+/// # Errors
+/// If there isn't one count per piece, or the region is over 128 both wide and high
+pub fn pack(
+    num_rows: usize,
+    num_cols: usize,
+    pieces: &[Polyomino],
+    counts: &[usize],
+) -> Result<Option<Packing>> {
+    if pieces.len() != counts.len() {
+        return Err(anyhow!(
+            "{} counts given for {} pieces",
+            counts.len(),
+            pieces.len()
+        ));
+    }
+    if num_rows.min(num_cols) > MAX_WIDTH {
+        return Err(anyhow!(
+            "region of {num_rows}x{num_cols} is too big to pack"
+        ));
+    }
+
+    let needed: usize = pieces.iter().zip(counts).map(|(p, n)| p.area * n).sum();
+    let Some(slack) = (num_rows * num_cols).checked_sub(needed) else {
+        return Ok(None);
+    };
+    if let Some(packing) = pack_in_boxes(num_rows, num_cols, pieces, counts) {
+        return Ok(Some(packing));
+    }
+
+    // Rows are searched one after another, so keeping them short keeps the frontier small
+    let transposed = num_cols > num_rows;
+    let (long, short) = (num_rows.max(num_cols), num_rows.min(num_cols));
+    let mut packer = Packer {
+        pieces,
+        num_cols: short,
+        reach: pieces.iter().map(Polyomino::extent).max().unwrap_or(1),
+        board: vec![0; long],
+        remaining: counts.to_vec(),
+        slack,
+        placed: Vec::new(),
+        dead_ends: HashSet::new(),
+    };
+    if !packer.search(0) {
+        return Ok(None);
+    }
+    let placements = packer
+        .placed
+        .iter()
+        .map(|&(piece, o, row, col)| {
+            let cells = pieces
+                .get(piece)
+                .and_then(|p| p.orientations.get(o))
+                .into_iter()
+                .flat_map(|o| o.cells(row, col))
+                .map(|p| {
+                    if transposed {
+                        Position::new(p.col(), p.row())
+                    } else {
+                        p
+                    }
+                })
+                .collect();
+            Placement { piece, cells }
+        })
+        .collect();
+    Ok(Some(Packing {
+        num_rows,
+        num_cols,
+        placements,
+    }))
+}
+
+/// Every piece in a square box of its own, if there are enough boxes to go round
+fn pack_in_boxes(
+    num_rows: usize,
+    num_cols: usize,
+    pieces: &[Polyomino],
+    counts: &[usize],
+) -> Option<Packing> {
+    let side = pieces
+        .iter()
+        .zip(counts)
+        .filter(|&(_, &n)| n > 0)
+        .map(|(p, _)| p.extent())
+        .max()
+        .unwrap_or(1);
+    let boxes_per_row = num_cols / side;
+    if counts.iter().sum::<usize>() > (num_rows / side) * boxes_per_row {
+        return None;
+    }
+
+    let placements = pieces
+        .iter()
+        .zip(counts)
+        .enumerate()
+        .flat_map(|(piece, (p, &n))| std::iter::repeat_n((piece, p), n))
+        .enumerate()
+        .map(|(slot, (piece, p))| {
+            let (row, col) = (slot / boxes_per_row * side, slot % boxes_per_row * side);
+            let cells = p
+                .orientations
+                .first()
+                .map(|o| o.cells(row, col).collect())
+                .unwrap_or_default();
+            Placement { piece, cells }
+        })
+        .collect();
+    Some(Packing {
+        num_rows,
+        num_cols,
+        placements,
+    })
+}
+
+/// Backtracking state for [`pack`], with a bitmask of filled cells per row of the region
+struct Packer<'a> {
+    pieces: &'a [Polyomino],
+    num_cols: usize,
+    /// Most rows a piece can span
+    reach: usize,
+    board: Vec<u128>,
+    remaining: Vec<usize>,
+    /// How many more cells can be left empty
+    slack: usize,
+    /// Piece, orientation and top left corner of everything placed so far
+    placed: Vec<(usize, usize, usize, usize)>,
+    /// States known not to work out. Everything before the first empty cell is settled and
+    /// nothing reaches beyond the rows a piece there could span, so those rows and the pieces
+    /// left are all that matter.
+    dead_ends: HashSet<(usize, Vec<u128>, Vec<usize>)>,
+}
+
+impl Packer<'_> {
+    /// Place the remaining pieces in the cells from `from` on, counting row by row
+    fn search(&mut self, from: usize) -> bool {
+        if self.remaining.iter().all(|&n| n == 0) {
+            return true;
+        }
+        let Some((row, col)) = self.first_empty(from) else {
+            return false;
+        };
+        let next = row * self.num_cols + col + 1;
+        let window = self
+            .board
+            .iter()
+            .skip(row)
+            .take(self.reach)
+            .copied()
+            .collect();
+        let state = (next, window, self.remaining.clone());
+        if self.dead_ends.contains(&state) {
+            return false;
+        }
+
+        for piece in 0..self.pieces.len() {
+            if self.remaining.get(piece) == Some(&0) {
+                continue;
+            }
+            let num_orientations = self
+                .pieces
+                .get(piece)
+                .map_or(0, Polyomino::num_orientations);
+            for o in 0..num_orientations {
+                let Some(left) = self.fits(piece, o, row, col) else {
+                    continue;
+                };
+                self.toggle(piece, o, row, left);
+                self.set_remaining(piece, -1);
+                self.placed.push((piece, o, row, left));
+                if self.search(next) {
+                    return true;
+                }
+                self.placed.pop();
+                self.set_remaining(piece, 1);
+                self.toggle(piece, o, row, left);
+            }
+        }
+
+        // Nothing goes here, which only works if the pieces don't need every cell
+        if self.slack > 0 {
+            self.slack -= 1;
+            self.toggle_cell(row, col);
+            if self.search(next) {
+                return true;
+            }
+            self.toggle_cell(row, col);
+            self.slack += 1;
+        }
+        self.dead_ends.insert(state);
+        false
+    }
+
+    fn first_empty(&self, from: usize) -> Option<(usize, usize)> {
+        let full = if self.num_cols == MAX_WIDTH {
+            u128::MAX
+        } else {
+            (1 << self.num_cols) - 1
+        };
+        let (start_row, start_col) = (from / self.num_cols, from % self.num_cols);
+        self.board
+            .iter()
+            .enumerate()
+            .skip(start_row)
+            .find_map(|(row, &filled)| {
+                let mut empty = !filled & full;
+                if row == start_row {
+                    empty &= full << start_col & full;
+                }
+                (empty != 0).then(|| (row, empty.trailing_zeros() as usize))
+            })
+    }
+
+    /// Left edge for orientation `o` of `piece` to cover `(row, col)` with its first cell, if
+    /// it fits there
+    fn fits(&self, piece: usize, o: usize, row: usize, col: usize) -> Option<usize> {
+        let orientation = self.pieces.get(piece)?.orientations.get(o)?;
+        let left = col.checked_sub(orientation.anchor)?;
+        if left + orientation.width > self.num_cols {
+            return None;
+        }
+        let rows = self.board.get(row..row + orientation.height())?;
+        rows.iter()
+            .zip(&orientation.rows)
+            .all(|(&filled, &mask)| filled & mask << left == 0)
+            .then_some(left)
+    }
+
+    /// Place or lift orientation `o` of `piece` with its top left at `(row, left)`
+    fn toggle(&mut self, piece: usize, o: usize, row: usize, left: usize) {
+        let Some(orientation) = self.pieces.get(piece).and_then(|p| p.orientations.get(o)) else {
+            return;
+        };
+        for (filled, &mask) in self.board.iter_mut().skip(row).zip(&orientation.rows) {
+            *filled ^= mask << left;
+        }
+    }
+
+    fn toggle_cell(&mut self, row: usize, col: usize) {
+        if let Some(filled) = self.board.get_mut(row) {
+            *filled ^= 1 << col;
+        }
+    }
+
+    fn set_remaining(&mut self, piece: usize, change: isize) {
+        if let Some(n) = self.remaining.get_mut(piece) {
+            *n = n.saturating_add_signed(change);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn piece(rows: &[&str]) -> Polyomino {
+        let cells = rows
+            .iter()
+            .map(|r| r.chars().map(|c| c == '#').collect())
+            .collect();
+        Polyomino::new(&Grid::new(cells)).expect("has cells")
+    }
+
+    fn example_pieces() -> Vec<Polyomino> {
+        vec![
+            piece(&["###", "##.", "##."]),
+            piece(&["###", "##.", ".##"]),
+            piece(&[".##", "###", "##."]),
+            piece(&["##.", "###", "##."]),
+            piece(&["###", "#..", "###"]),
+            piece(&["###", ".#.", "###"]),
+        ]
+    }
+
+    /// Check a packing uses each piece the right number of times, without overlaps
+    fn check(packing: &Packing, pieces: &[Polyomino], counts: &[usize]) {
+        let mut used = vec![0; counts.len()];
+        let mut covered = HashSet::new();
+        for placement in packing.placements() {
+            *used.get_mut(placement.piece()).expect("known piece") += 1;
+            let area = pieces.get(placement.piece()).map(Polyomino::area);
+            assert_eq!(area, Some(placement.cells().len()));
+            for p in placement.cells() {
+                assert!(covered.insert(*p), "{p:?} covered twice");
+            }
+        }
+        assert_eq!(counts, used);
+    }
+
+    #[test]
+    fn test_orientations() {
+        assert_eq!(8, piece(&["#..", "###"]).num_orientations());
+        assert_eq!(4, piece(&["###", ".#."]).num_orientations());
+        assert_eq!(1, piece(&["##", "##"]).num_orientations());
+        // Padding around the piece makes no difference
+        assert_eq!(2, piece(&["....", ".##.", "...."]).num_orientations());
+        assert!(Polyomino::new(&Grid::new(vec![vec![false]])).is_err());
+    }
+
+    #[test]
+    fn test_example_regions() {
+        let pieces = example_pieces();
+        for (rows, cols, counts) in [(4, 4, [0, 0, 0, 0, 2, 0]), (5, 12, [1, 0, 1, 0, 2, 2])] {
+            let packing = pack(rows, cols, &pieces, &counts)
+                .expect("valid")
+                .expect("fits");
+            check(&packing, &pieces, &counts);
+        }
+        let too_many = pack(5, 12, &pieces, &[1, 0, 1, 0, 3, 2]).expect("valid");
+        assert!(too_many.is_none());
+    }
+
+    #[test]
+    fn test_boxes_and_area() {
+        let pieces = example_pieces();
+        let counts = [1, 1, 1, 1, 0, 0];
+        let packing = pack(6, 6, &pieces, &counts)
+            .expect("valid")
+            .expect("a box each");
+        check(&packing, &pieces, &counts);
+        assert!(
+            pack(3, 3, &pieces, &[0, 0, 0, 0, 0, 2])
+                .expect("valid")
+                .is_none()
+        );
+        assert!(pack(3, 3, &pieces, &[1]).is_err());
+    }
+
+    #[test]
+    fn test_render() {
+        let pieces = [piece(&["###"]), piece(&["#"])];
+        let packing = pack(2, 4, &pieces, &[2, 1]).expect("valid").expect("fits");
+        assert_eq!("AAAC\nBBB.\n", packing.render());
+    }
+}
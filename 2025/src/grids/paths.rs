@@ -0,0 +1,289 @@
+use crate::grids::{Connectivity, Grid, Position};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Visit {
+    cost: u64,
+    prev: Option<Position>,
+}
+
+/// Shortest paths found from a start position, as produced by [`bfs`] and [`dijkstra`]
+pub struct PathTree {
+    start: Position,
+    visits: Grid<Option<Visit>>,
+}
+
+impl PathTree {
+    fn new<T>(grid: &Grid<T>, start: Position) -> Self {
+        let visits = grid
+            .0
+            .iter()
+            .map(|row| row.iter().map(|_| None).collect())
+            .collect();
+        PathTree {
+            start,
+            visits: Grid::new(visits),
+        }
+    }
+
+    #[must_use]
+    pub fn start(&self) -> Position {
+        self.start
+    }
+
+    /// Cost of the cheapest path to `p`, or `None` if it can't be reached
+    #[must_use]
+    pub fn cost_to(&self, p: &Position) -> Option<u64> {
+        self.visit(p).map(|v| v.cost)
+    }
+
+    /// Positions along the cheapest path to `p`, including both the start and `p` itself
+    #[must_use]
+    pub fn path_to(&self, p: &Position) -> Option<Vec<Position>> {
+        let mut path = vec![*p];
+        let mut curr = self.visit(p)?;
+        while let Some(prev) = curr.prev {
+            path.push(prev);
+            curr = self.visit(&prev).expect("predecessors are always visited");
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    /// Every reachable position along with the cost to reach it
+    pub fn reachable(&self) -> impl Iterator<Item = (Position, u64)> + '_ {
+        self.visits
+            .all_positions()
+            .filter_map(|p| self.cost_to(&p).map(|cost| (p, cost)))
+    }
+
+    fn visit(&self, p: &Position) -> Option<Visit> {
+        self.visits.get(p).copied().flatten()
+    }
+
+    fn record(&mut self, p: &Position, cost: u64, prev: Option<Position>) {
+        self.visits
+            .set(p, Some(Visit { cost, prev }))
+            .expect("only positions in the grid are visited");
+    }
+}
+
+/// Breadth-first search from `start`, where each step costs one. `passable(from, to, value)`
+/// decides whether a step from `from` to the neighbor `to` holding `value` is allowed.
+pub fn bfs<T, F>(
+    grid: &Grid<T>,
+    start: Position,
+    connectivity: Connectivity,
+    passable: F,
+) -> PathTree
+where
+    F: Fn(&Position, &Position, &T) -> bool,
+{
+    let mut tree = PathTree::new(grid, start);
+    if grid.get(&start).is_none() {
+        return tree;
+    }
+
+    tree.record(&start, 0, None);
+    let mut queue = VecDeque::from([(start, 0)]);
+    while let Some((curr, dist)) = queue.pop_front() {
+        for next in connectivity.neighbors(&curr) {
+            let Some(val) = grid.get(&next) else {
+                continue;
+            };
+            if tree.visit(&next).is_none() && passable(&curr, &next, val) {
+                tree.record(&next, dist + 1, Some(curr));
+                queue.push_back((next, dist + 1));
+            }
+        }
+    }
+    tree
+}
+
+/// Dijkstra's algorithm from `start`. `cost(from, to, value)` gives the cost of stepping from
+/// `from` to the neighbor `to` holding `value`, or `None` if that step isn't allowed.
+pub fn dijkstra<T, F>(
+    grid: &Grid<T>,
+    start: Position,
+    connectivity: Connectivity,
+    cost: F,
+) -> PathTree
+where
+    F: Fn(&Position, &Position, &T) -> Option<u64>,
+{
+    best_first(grid, start, None, connectivity, cost)
+}
+
+/// A* search from `start` to `goal`, with `cost` as for [`dijkstra`]. Returns the cost of the
+/// cheapest path and the positions along it, both ends included.
+///
+/// The heuristic is the Manhattan distance for [`Connectivity::Four`] and the Chebyshev distance
+/// for [`Connectivity::Eight`], so the result is only guaranteed optimal if every step costs at
+/// least one.
+pub fn astar<T, F>(
+    grid: &Grid<T>,
+    start: Position,
+    goal: Position,
+    connectivity: Connectivity,
+    cost: F,
+) -> Option<(u64, Vec<Position>)>
+where
+    F: Fn(&Position, &Position, &T) -> Option<u64>,
+{
+    let tree = best_first(grid, start, Some(goal), connectivity, cost);
+    Some((tree.cost_to(&goal)?, tree.path_to(&goal)?))
+}
+
+/// Dijkstra's algorithm, turned into A* when there's a goal to aim for. Stops as soon as the goal
+/// is settled.
+fn best_first<T, F>(
+    grid: &Grid<T>,
+    start: Position,
+    goal: Option<Position>,
+    connectivity: Connectivity,
+    cost: F,
+) -> PathTree
+where
+    F: Fn(&Position, &Position, &T) -> Option<u64>,
+{
+    let heuristic = |p: &Position| -> u64 {
+        let estimate = match (goal, connectivity) {
+            (None, _) => 0,
+            (Some(goal), Connectivity::Four) => p.manhattan_distance(&goal),
+            (Some(goal), Connectivity::Eight) => p.chebyshev_distance(&goal),
+        };
+        estimate as u64
+    };
+
+    let mut tree = PathTree::new(grid, start);
+    if grid.get(&start).is_none() {
+        return tree;
+    }
+
+    tree.record(&start, 0, None);
+    let mut heap = BinaryHeap::from([Reverse((heuristic(&start), 0, start.row, start.col))]);
+    while let Some(Reverse((_, dist, row, col))) = heap.pop() {
+        let curr = Position::new(row, col);
+        if tree.cost_to(&curr).is_some_and(|best| best < dist) {
+            // Stale entry, a cheaper path was already found
+            continue;
+        }
+        if goal == Some(curr) {
+            break;
+        }
+
+        for next in connectivity.neighbors(&curr) {
+            let Some(step) = grid.get(&next).and_then(|val| cost(&curr, &next, val)) else {
+                continue;
+            };
+            let next_dist = dist + step;
+            if tree.cost_to(&next).is_none_or(|best| next_dist < best) {
+                tree.record(&next, next_dist, Some(curr));
+                heap.push(Reverse((
+                    next_dist + heuristic(&next),
+                    next_dist,
+                    next.row,
+                    next.col,
+                )));
+            }
+        }
+    }
+    tree
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAZE: &str = "\
+S.#.....
+.##.###.
+....#...
+###.#.#E
+";
+
+    fn maze() -> Grid<char> {
+        Grid::new(MAZE.lines().map(|line| line.chars().collect()).collect())
+    }
+
+    fn open(_: &Position, _: &Position, c: &char) -> bool {
+        *c != '#'
+    }
+
+    #[test]
+    fn test_bfs() {
+        let grid = maze();
+        let tree = bfs(&grid, Position::new(0, 0), Connectivity::Four, open);
+        assert_eq!(Some(0), tree.cost_to(&Position::new(0, 0)));
+        assert_eq!(Some(5), tree.cost_to(&Position::new(2, 3)));
+        assert_eq!(Some(14), tree.cost_to(&Position::new(3, 7)));
+        assert_eq!(None, tree.cost_to(&Position::new(0, 2)));
+        assert_eq!(None, tree.cost_to(&Position::new(10, 10)));
+
+        let path = tree.path_to(&Position::new(2, 3)).expect("reachable");
+        assert_eq!(
+            vec![
+                Position::new(0, 0),
+                Position::new(1, 0),
+                Position::new(2, 0),
+                Position::new(2, 1),
+                Position::new(2, 2),
+                Position::new(2, 3),
+            ],
+            path
+        );
+        assert_eq!(20, tree.reachable().count());
+    }
+
+    #[test]
+    fn test_bfs_eight_connected() {
+        let grid = maze();
+        let tree = bfs(&grid, Position::new(0, 0), Connectivity::Eight, open);
+        assert_eq!(Some(4), tree.cost_to(&Position::new(2, 3)));
+        assert_eq!(Some(10), tree.cost_to(&Position::new(3, 7)));
+    }
+
+    #[test]
+    fn test_dijkstra() {
+        // Moving down is free, any other step costs 10
+        let grid = maze();
+        let tree = dijkstra(
+            &grid,
+            Position::new(0, 0),
+            Connectivity::Four,
+            |from, to, &c| (c != '#').then_some(if to.row() > from.row() { 0 } else { 10 }),
+        );
+        assert_eq!(Some(0), tree.cost_to(&Position::new(2, 0)));
+        assert_eq!(Some(30), tree.cost_to(&Position::new(3, 3)));
+        assert_eq!(Some(50), tree.cost_to(&Position::new(0, 3)));
+    }
+
+    #[test]
+    fn test_astar() {
+        let grid = maze();
+        let (cost, path) = astar(
+            &grid,
+            Position::new(0, 0),
+            Position::new(3, 7),
+            Connectivity::Four,
+            |_, _, &c| (c != '#').then_some(1),
+        )
+        .expect("reachable");
+        assert_eq!(14, cost);
+        assert_eq!(15, path.len());
+        assert_eq!(Some(&Position::new(0, 0)), path.first());
+        assert_eq!(Some(&Position::new(3, 7)), path.last());
+
+        assert!(
+            astar(
+                &grid,
+                Position::new(0, 0),
+                Position::new(0, 2),
+                Connectivity::Four,
+                |_, _, &c| (c != '#').then_some(1),
+            )
+            .is_none()
+        );
+    }
+}
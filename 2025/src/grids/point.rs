@@ -0,0 +1,190 @@
+use crate::grids::Position;
+use crate::grids::sparse::Neighbors;
+use std::ops::{Add, Sub};
+
+/// Integer point in `N` dimensions, for puzzles in 3D, 4D and so on or that need negative
+/// coordinates
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Point<const N: usize> {
+    coords: [i64; N],
+}
+
+impl<const N: usize> Point<N> {
+    #[must_use]
+    pub fn new(coords: [i64; N]) -> Self {
+        Point { coords }
+    }
+
+    #[must_use]
+    pub fn origin() -> Self {
+        Point { coords: [0; N] }
+    }
+
+    #[must_use]
+    pub fn coords(&self) -> &[i64; N] {
+        &self.coords
+    }
+
+    /// Coordinate along the given axis, 0 being x
+    #[must_use]
+    pub fn get(&self, axis: usize) -> Option<i64> {
+        self.coords.get(axis).copied()
+    }
+
+    #[must_use]
+    pub fn manhattan_distance(&self, other: &Self) -> u64 {
+        self.axis_distances(other).sum()
+    }
+
+    #[must_use]
+    pub fn chebyshev_distance(&self, other: &Self) -> u64 {
+        self.axis_distances(other).max().unwrap_or(0)
+    }
+
+    #[must_use]
+    pub fn squared_euclidean_distance(&self, other: &Self) -> u64 {
+        self.axis_distances(other).map(|d| d * d).sum()
+    }
+
+    fn axis_distances<'a>(&'a self, other: &'a Self) -> impl Iterator<Item = u64> + 'a {
+        self.coords
+            .iter()
+            .zip(&other.coords)
+            .map(|(a, b)| a.abs_diff(*b))
+    }
+
+    /// The 2N points one step away along a single axis
+    pub fn orthogonal(&self) -> impl Iterator<Item = Self> + '_ {
+        (0..N).flat_map(move |axis| {
+            [-1, 1].into_iter().map(move |step| {
+                let mut next = *self;
+                if let Some(c) = next.coords.get_mut(axis) {
+                    *c += step;
+                }
+                next
+            })
+        })
+    }
+
+    /// The 3^N - 1 points that differ by at most one along every axis
+    pub fn adjacent(&self) -> impl Iterator<Item = Self> + '_ {
+        let num_offsets = 3usize.pow(N as u32);
+        (0..num_offsets)
+            // The middle index is the all-zero offset, i.e. the point itself
+            .filter(move |&idx| idx != num_offsets / 2)
+            .map(move |idx| {
+                let mut next = *self;
+                let mut rest = idx;
+                for c in &mut next.coords {
+                    *c += (rest % 3) as i64 - 1;
+                    rest /= 3;
+                }
+                next
+            })
+    }
+}
+
+impl Point<2> {
+    #[must_use]
+    pub fn x(&self) -> i64 {
+        self.coords[0]
+    }
+
+    #[must_use]
+    pub fn y(&self) -> i64 {
+        self.coords[1]
+    }
+}
+
+impl Point<3> {
+    #[must_use]
+    pub fn x(&self) -> i64 {
+        self.coords[0]
+    }
+
+    #[must_use]
+    pub fn y(&self) -> i64 {
+        self.coords[1]
+    }
+
+    #[must_use]
+    pub fn z(&self) -> i64 {
+        self.coords[2]
+    }
+}
+
+impl<const N: usize> Add for Point<N> {
+    type Output = Self;
+
+    fn add(mut self, rhs: Self) -> Self {
+        for (a, b) in self.coords.iter_mut().zip(rhs.coords) {
+            *a += b;
+        }
+        self
+    }
+}
+
+impl<const N: usize> Sub for Point<N> {
+    type Output = Self;
+
+    fn sub(mut self, rhs: Self) -> Self {
+        for (a, b) in self.coords.iter_mut().zip(rhs.coords) {
+            *a -= b;
+        }
+        self
+    }
+}
+
+/// Grid positions map to (x, y) = (col, row)
+impl From<&Position> for Point<2> {
+    fn from(p: &Position) -> Self {
+        Point::new([p.col() as i64, p.row() as i64])
+    }
+}
+
+impl<const N: usize> Neighbors for Point<N> {
+    fn neighbors(&self) -> impl Iterator<Item = Self> {
+        self.adjacent()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distances() {
+        let a = Point::new([1, -2, 3]);
+        let b = Point::new([-1, 2, 4]);
+        assert_eq!(7, a.manhattan_distance(&b));
+        assert_eq!(4, a.chebyshev_distance(&b));
+        assert_eq!(21, a.squared_euclidean_distance(&b));
+        assert_eq!(0, a.manhattan_distance(&a));
+    }
+
+    #[test]
+    fn test_neighbors() {
+        let p = Point::new([0, 0, 0, 0]);
+        assert_eq!(8, p.orthogonal().count());
+        assert_eq!(80, p.adjacent().count());
+        assert!(
+            p.adjacent()
+                .all(|n| n != p && n.chebyshev_distance(&p) == 1)
+        );
+
+        assert_eq!(
+            vec![Point::new([4]), Point::new([6])],
+            Point::new([5]).adjacent().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let a = Point::new([1, 2]);
+        let b = Point::new([3, -4]);
+        assert_eq!(Point::new([4, -2]), a + b);
+        assert_eq!(Point::new([-2, 6]), a - b);
+        assert_eq!((1, 2), (a.x(), a.y()));
+        assert_eq!(Point::new([3, 1]), Point::from(&Position::new(1, 3)));
+    }
+}
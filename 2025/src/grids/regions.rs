@@ -0,0 +1,233 @@
+use crate::grids::{Connectivity, Grid, Position};
+
+/// A connected set of grid positions, as found by [`flood_fill`] or [`label_components`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Component {
+    positions: Vec<Position>,
+    perimeter: usize,
+}
+
+impl Component {
+    /// Positions in the order they were reached, starting from the seed
+    #[must_use]
+    pub fn positions(&self) -> &[Position] {
+        &self.positions
+    }
+
+    #[must_use]
+    pub fn area(&self) -> usize {
+        self.positions.len()
+    }
+
+    /// Number of cell sides that don't border another cell of the component, counting the edge of
+    /// the grid. This is always in terms of the four orthogonal sides, even for components found
+    /// with [`Connectivity::Eight`].
+    #[must_use]
+    pub fn perimeter(&self) -> usize {
+        self.perimeter
+    }
+
+    /// Top-left and bottom-right corners of the smallest rectangle containing the component
+    #[must_use]
+    pub fn bounding_box(&self) -> (Position, Position) {
+        let min_row = self.positions.iter().map(Position::row).min();
+        let max_row = self.positions.iter().map(Position::row).max();
+        let min_col = self.positions.iter().map(Position::col).min();
+        let max_col = self.positions.iter().map(Position::col).max();
+        match (min_row, max_row, min_col, max_col) {
+            (Some(min_row), Some(max_row), Some(min_col), Some(max_col)) => (
+                Position::new(min_row, min_col),
+                Position::new(max_row, max_col),
+            ),
+            _ => unreachable!("components are never empty"),
+        }
+    }
+}
+
+/// Every connected component of a grid, with each position labeled by the index of the
+/// component it belongs to
+pub struct Components {
+    labels: Grid<Option<usize>>,
+    components: Vec<Component>,
+}
+
+impl Components {
+    /// Index into [`Components::all`] of the component containing `p`
+    #[must_use]
+    pub fn label_of(&self, p: &Position) -> Option<usize> {
+        self.labels.get(p).copied().flatten()
+    }
+
+    #[must_use]
+    pub fn get(&self, label: usize) -> Option<&Component> {
+        self.components.get(label)
+    }
+
+    /// Components in order of their first position in row-major order
+    #[must_use]
+    pub fn all(&self) -> &[Component] {
+        &self.components
+    }
+}
+
+/// Find all positions connected to `seed`. Two neighboring positions are connected when
+/// `same(a, b)` holds for their values, which should be an equivalence relation. Returns `None` if
+/// the seed is outside the grid.
+pub fn flood_fill<T, F>(
+    grid: &Grid<T>,
+    seed: Position,
+    connectivity: Connectivity,
+    same: F,
+) -> Option<Component>
+where
+    F: Fn(&T, &T) -> bool,
+{
+    grid.get(&seed)?;
+    let mut labels = empty_labels(grid);
+    Some(fill(grid, seed, connectivity, &same, &mut labels, 0))
+}
+
+/// Split the whole grid into connected components, as for [`flood_fill`]
+pub fn label_components<T, F>(grid: &Grid<T>, connectivity: Connectivity, same: F) -> Components
+where
+    F: Fn(&T, &T) -> bool,
+{
+    let mut labels = empty_labels(grid);
+    let mut components = Vec::new();
+    for pos in grid.all_positions() {
+        if labels.get(&pos).copied().flatten().is_none() {
+            let component = fill(
+                grid,
+                pos,
+                connectivity,
+                &same,
+                &mut labels,
+                components.len(),
+            );
+            components.push(component);
+        }
+    }
+    Components { labels, components }
+}
+
+fn empty_labels<T>(grid: &Grid<T>) -> Grid<Option<usize>> {
+    Grid::new(
+        grid.0
+            .iter()
+            .map(|row| row.iter().map(|_| None).collect())
+            .collect(),
+    )
+}
+
+fn fill<T, F>(
+    grid: &Grid<T>,
+    seed: Position,
+    connectivity: Connectivity,
+    same: &F,
+    labels: &mut Grid<Option<usize>>,
+    label: usize,
+) -> Component
+where
+    F: Fn(&T, &T) -> bool,
+{
+    labels.set(&seed, Some(label)).expect("seed is in the grid");
+    let mut positions = vec![seed];
+    let mut stack = vec![seed];
+    while let Some(curr) = stack.pop() {
+        let Some(curr_val) = grid.get(&curr) else {
+            continue;
+        };
+        for next in connectivity.neighbors(&curr) {
+            let unlabeled = labels.get(&next).is_some_and(Option::is_none);
+            if unlabeled && grid.get(&next).is_some_and(|val| same(curr_val, val)) {
+                labels
+                    .set(&next, Some(label))
+                    .expect("neighbor is in the grid");
+                positions.push(next);
+                stack.push(next);
+            }
+        }
+    }
+
+    let perimeter = positions
+        .iter()
+        .map(|p| {
+            let shared_sides = p
+                .orthogonal()
+                .filter(|n| labels.get(n).copied().flatten() == Some(label))
+                .count();
+            4 - shared_sides
+        })
+        .sum();
+    Component {
+        positions,
+        perimeter,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GARDEN: &str = "\
+AAAA
+BBCD
+BBCC
+EEEC
+";
+
+    fn garden() -> Grid<char> {
+        Grid::new(GARDEN.lines().map(|line| line.chars().collect()).collect())
+    }
+
+    #[test]
+    fn test_flood_fill() {
+        let grid = garden();
+        let region = flood_fill(&grid, Position::new(1, 2), Connectivity::Four, char::eq)
+            .expect("seed in grid");
+        assert_eq!(4, region.area());
+        assert_eq!(10, region.perimeter());
+        assert_eq!(
+            (Position::new(1, 2), Position::new(3, 3)),
+            region.bounding_box()
+        );
+
+        assert!(flood_fill(&grid, Position::new(4, 0), Connectivity::Four, char::eq).is_none());
+    }
+
+    #[test]
+    fn test_label_components() {
+        let grid = garden();
+        let components = label_components(&grid, Connectivity::Four, char::eq);
+        assert_eq!(5, components.all().len());
+        assert_eq!(
+            vec![(4, 10), (4, 8), (4, 10), (1, 4), (3, 8)],
+            components
+                .all()
+                .iter()
+                .map(|c| (c.area(), c.perimeter()))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            components.label_of(&Position::new(2, 3)),
+            components.label_of(&Position::new(1, 2))
+        );
+        assert_eq!(None, components.label_of(&Position::new(0, 4)));
+    }
+
+    #[test]
+    fn test_label_components_eight_connected() {
+        let grid = Grid::new(vec![
+            vec![true, false, false], // force multi-line format
+            vec![false, true, false],
+            vec![false, false, true],
+        ]);
+        let four = label_components(&grid, Connectivity::Four, bool::eq);
+        assert_eq!(5, four.all().len());
+
+        let eight = label_components(&grid, Connectivity::Eight, bool::eq);
+        assert_eq!(2, eight.all().len());
+        assert_eq!(Some(3), eight.get(0).map(Component::area));
+        assert_eq!(Some(12), eight.get(0).map(Component::perimeter));
+    }
+}
@@ -0,0 +1,83 @@
+use crate::grids::Position;
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+/// Coordinates that know their neighbors, so cells can live in a hash set instead of a bounded
+/// grid
+pub trait Neighbors: Copy + Eq + Hash {
+    fn neighbors(&self) -> impl Iterator<Item = Self>;
+}
+
+impl Neighbors for Position {
+    fn neighbors(&self) -> impl Iterator<Item = Self> {
+        self.adjacent()
+    }
+}
+
+/// Advance a cellular automaton stored as its set of live cells by one step. `rule(alive, n)`
+/// decides whether a cell with `n` live neighbors is alive next step. Only cells that are alive or
+/// have a live neighbor are considered, so a cell with no live neighbors can never come alive.
+pub fn automaton_step<P, F>(alive: &HashSet<P>, rule: F) -> HashSet<P>
+where
+    P: Neighbors,
+    F: Fn(bool, usize) -> bool,
+{
+    let mut live_neighbors: HashMap<P, usize> = alive.iter().map(|p| (*p, 0)).collect();
+    for p in alive {
+        for n in p.neighbors() {
+            *live_neighbors.entry(n).or_insert(0) += 1;
+        }
+    }
+    live_neighbors
+        .into_iter()
+        .filter(|(p, n)| rule(alive.contains(p), *n))
+        .map(|(p, _)| p)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grids::hex::HexCoord;
+    use crate::grids::point::Point;
+
+    fn life(alive: bool, n: usize) -> bool {
+        n == 3 || (alive && n == 2)
+    }
+
+    #[test]
+    fn test_blinker() {
+        let horizontal: HashSet<_> = [Point::new([-1, 0]), Point::new([0, 0]), Point::new([1, 0])]
+            .into_iter()
+            .collect();
+        let vertical: HashSet<_> = [Point::new([0, -1]), Point::new([0, 0]), Point::new([0, 1])]
+            .into_iter()
+            .collect();
+        assert_eq!(vertical, automaton_step(&horizontal, life));
+        assert_eq!(horizontal, automaton_step(&vertical, life));
+    }
+
+    #[test]
+    fn test_positions() {
+        let block: HashSet<_> = [(0, 0), (0, 1), (1, 0), (1, 1)]
+            .into_iter()
+            .map(|(r, c)| Position::new(r, c))
+            .collect();
+        assert_eq!(block, automaton_step(&block, life));
+    }
+
+    #[test]
+    fn test_hex() {
+        // Every hex adjacent to exactly two live hexes comes alive
+        let alive: HashSet<_> = [HexCoord::new(0, 0), HexCoord::new(1, 0)]
+            .into_iter()
+            .collect();
+        let next = automaton_step(&alive, |_, n| n == 2);
+        assert_eq!(
+            [HexCoord::new(1, -1), HexCoord::new(0, 1)]
+                .into_iter()
+                .collect::<HashSet<_>>(),
+            next
+        );
+    }
+}
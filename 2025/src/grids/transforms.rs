@@ -0,0 +1,245 @@
+use crate::grids::{Grid, Position};
+use anyhow::{Result, anyhow};
+
+/// Rearrangements of a grid's cells. These treat the grid as `num_rows` x `num_cols`, with cells
+/// missing from short rows skipped, so pad jagged input with [`Grid::from_jagged`] first if the
+/// columns need to stay aligned.
+impl<T: Clone> Grid<T> {
+    /// Build a rectangular grid, padding rows shorter than the longest one with `fill`
+    #[must_use]
+    pub fn from_jagged(mut g: Vec<Vec<T>>, fill: T) -> Self {
+        let num_cols = g.iter().map(Vec::len).max().unwrap_or(0);
+        for row in &mut g {
+            row.resize(num_cols, fill.clone());
+        }
+        Grid(g)
+    }
+
+    /// Swap rows and columns, so row `r` of the result is column `r` of this grid
+    #[must_use]
+    pub fn transpose(&self) -> Self {
+        Grid(
+            (0..self.num_cols())
+                .map(|col| {
+                    self.0
+                        .iter()
+                        .filter_map(|row| row.get(col).cloned())
+                        .collect()
+                })
+                .collect(),
+        )
+    }
+
+    /// Mirror left to right
+    #[must_use]
+    pub fn flip_horizontal(&self) -> Self {
+        Grid(
+            self.0
+                .iter()
+                .map(|row| row.iter().rev().cloned().collect())
+                .collect(),
+        )
+    }
+
+    /// Mirror top to bottom
+    #[must_use]
+    pub fn flip_vertical(&self) -> Self {
+        Grid(self.0.iter().rev().cloned().collect())
+    }
+
+    /// Rotate a quarter turn clockwise
+    #[must_use]
+    pub fn rotate_90(&self) -> Self {
+        self.flip_vertical().transpose()
+    }
+
+    #[must_use]
+    pub fn rotate_180(&self) -> Self {
+        self.flip_vertical().flip_horizontal()
+    }
+
+    /// Rotate three quarter turns clockwise, i.e. a quarter turn counter-clockwise
+    #[must_use]
+    pub fn rotate_270(&self) -> Self {
+        self.transpose().flip_vertical()
+    }
+
+    /// Copy of the `num_rows` x `num_cols` block with its top-left corner at `top_left`
+    pub fn crop(&self, top_left: &Position, num_rows: usize, num_cols: usize) -> Result<Self> {
+        let rows = top_left
+            .row
+            .checked_add(num_rows)
+            .and_then(|end| self.0.get(top_left.row..end))
+            .ok_or_else(|| anyhow!("crop rows out of bounds"))?;
+        let col_end = top_left
+            .col
+            .checked_add(num_cols)
+            .ok_or_else(|| anyhow!("crop columns out of bounds"))?;
+        rows.iter()
+            .map(|row| {
+                row.get(top_left.col..col_end)
+                    .map(<[T]>::to_vec)
+                    .ok_or_else(|| anyhow!("crop columns out of bounds"))
+            })
+            .collect::<Result<_>>()
+            .map(Grid)
+    }
+
+    /// Every `size` x `size` block of the grid along with its top-left corner, in row-major order
+    pub fn windows(&self, size: usize) -> impl Iterator<Item = (Position, Self)> + '_ {
+        let (row_starts, col_starts) = if size == 0 {
+            (0, 0)
+        } else {
+            (
+                (self.num_rows() + 1).saturating_sub(size),
+                (self.num_cols() + 1).saturating_sub(size),
+            )
+        };
+        (0..row_starts)
+            .flat_map(move |row| (0..col_starts).map(move |col| Position::new(row, col)))
+            .filter_map(move |pos| self.crop(&pos, size, size).ok().map(|w| (pos, w)))
+    }
+}
+
+impl<T: Clone + PartialEq> Grid<T> {
+    /// The distinct grids reachable by rotating and flipping, this one included. There are at
+    /// most eight, fewer if the grid has some symmetry.
+    #[must_use]
+    pub fn orientations(&self) -> Vec<Self> {
+        let mut result: Vec<Self> = Vec::with_capacity(8);
+        let mut curr = self.clone();
+        for _ in 0..4 {
+            for candidate in [curr.clone(), curr.flip_horizontal()] {
+                if !result.contains(&candidate) {
+                    result.push(candidate);
+                }
+            }
+            curr = curr.rotate_90();
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::grids::{Grid, Position};
+
+    fn grid() -> Grid<u8> {
+        Grid::new(vec![
+            vec![1, 2, 3], // force multi-line format
+            vec![4, 5, 6],
+        ])
+    }
+
+    #[test]
+    fn test_from_jagged() {
+        assert_eq!(
+            Grid::new(vec![
+                vec!['a', 'b'], // force multi-line format
+                vec!['c', ' '],
+            ]),
+            Grid::from_jagged(vec![vec!['a', 'b'], vec!['c']], ' ')
+        );
+    }
+
+    #[test]
+    fn test_transpose() {
+        assert_eq!(
+            Grid::new(vec![
+                vec![1, 4], // force multi-line format
+                vec![2, 5],
+                vec![3, 6],
+            ]),
+            grid().transpose()
+        );
+    }
+
+    #[test]
+    fn test_flips() {
+        assert_eq!(
+            Grid::new(vec![
+                vec![3, 2, 1], // force multi-line format
+                vec![6, 5, 4],
+            ]),
+            grid().flip_horizontal()
+        );
+        assert_eq!(
+            Grid::new(vec![
+                vec![4, 5, 6], // force multi-line format
+                vec![1, 2, 3],
+            ]),
+            grid().flip_vertical()
+        );
+    }
+
+    #[test]
+    fn test_rotations() {
+        assert_eq!(
+            Grid::new(vec![
+                vec![4, 1], // force multi-line format
+                vec![5, 2],
+                vec![6, 3],
+            ]),
+            grid().rotate_90()
+        );
+        assert_eq!(
+            Grid::new(vec![
+                vec![6, 5, 4], // force multi-line format
+                vec![3, 2, 1],
+            ]),
+            grid().rotate_180()
+        );
+        assert_eq!(
+            Grid::new(vec![
+                vec![3, 6], // force multi-line format
+                vec![2, 5],
+                vec![1, 4],
+            ]),
+            grid().rotate_270()
+        );
+        assert_eq!(grid(), grid().rotate_90().rotate_270());
+    }
+
+    #[test]
+    fn test_orientations() {
+        let orientations = grid().orientations();
+        assert_eq!(8, orientations.len());
+        assert_eq!(Some(&grid()), orientations.first());
+
+        let symmetric = Grid::new(vec![
+            vec![true, true], // force multi-line format
+            vec![true, false],
+        ]);
+        assert_eq!(4, symmetric.orientations().len());
+    }
+
+    #[test]
+    fn test_crop() {
+        assert_eq!(
+            Grid::new(vec![vec![5, 6]]),
+            grid().crop(&Position::new(1, 1), 1, 2).expect("in bounds")
+        );
+        assert!(grid().crop(&Position::new(1, 1), 2, 1).is_err());
+        assert!(grid().crop(&Position::new(0, 2), 1, 2).is_err());
+        assert!(grid().crop(&Position::new(1, 0), usize::MAX, 1).is_err());
+        assert!(grid().crop(&Position::new(0, 1), 1, usize::MAX).is_err());
+    }
+
+    #[test]
+    fn test_windows() {
+        let windows: Vec<_> = grid().windows(2).collect();
+        assert_eq!(2, windows.len());
+        assert_eq!(
+            (
+                Position::new(0, 1),
+                Grid::new(vec![
+                    vec![2, 3], // force multi-line format
+                    vec![5, 6],
+                ])
+            ),
+            windows.get(1).cloned().expect("two windows")
+        );
+        assert_eq!(0, grid().windows(3).count());
+        assert_eq!(6, grid().windows(1).count());
+    }
+}
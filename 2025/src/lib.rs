@@ -1,4 +1,10 @@
+pub mod geometry;
+pub mod graph;
 pub mod grids;
+pub mod linear;
+pub mod polygon;
+pub mod terminal;
+pub mod visualize;
 
 pub fn start_day(day: &str) {
     println!("Advent of Code 2025 - Day {:0>2}", day);
@@ -0,0 +1,454 @@
+use anyhow::{Result, anyhow};
+
+pub mod gf2;
+
+/// A system of linear equations `a x = b` with integer coefficients, one row of `a` per equation
+#[derive(Debug, Clone)]
+pub struct LinearSystem {
+    rows: Vec<Vec<i64>>,
+    rhs: Vec<i64>,
+    num_variables: usize,
+}
+
+impl LinearSystem {
+    /// # Errors
+    /// If there isn't one right hand side per row, or the rows differ in length
+    pub fn new(rows: Vec<Vec<i64>>, rhs: Vec<i64>) -> Result<Self> {
+        if rows.len() != rhs.len() {
+            return Err(anyhow!(
+                "{} equations but {} right hand sides",
+                rows.len(),
+                rhs.len()
+            ));
+        }
+        let num_variables = rows.first().map_or(0, Vec::len);
+        if rows.iter().any(|r| r.len() != num_variables) {
+            return Err(anyhow!("every equation needs {num_variables} coefficients"));
+        }
+        Ok(LinearSystem {
+            rows,
+            rhs,
+            num_variables,
+        })
+    }
+
+    #[must_use]
+    pub fn num_variables(&self) -> usize {
+        self.num_variables
+    }
+
+    #[must_use]
+    pub fn num_equations(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Whether `x` satisfies every equation
+    #[must_use]
+    pub fn is_solution(&self, x: &[u64]) -> bool {
+        x.len() == self.num_variables
+            && self.rows.iter().zip(&self.rhs).all(|(row, &b)| {
+                let lhs: i128 = row
+                    .iter()
+                    .zip(x)
+                    .map(|(&a, &v)| i128::from(a) * i128::from(v))
+                    .sum();
+                lhs == i128::from(b)
+            })
+    }
+
+    /// The solution in non-negative integers with the smallest sum, or `None` if there isn't one.
+    ///
+    /// Gaussian elimination splits the variables into pivots, each fixed by the others, and free
+    /// variables. The free variables are then enumerated between zero and a bound taken from
+    /// equations with no negative coefficients, which must exist for each of them.
+    /// # Errors
+    /// If a free variable has no such bound, or the elimination overflows
+    pub fn min_sum_solution(&self) -> Result<Option<Vec<u64>>> {
+        // An equation of non-negative terms can't add up to less than zero
+        let all_non_negative = |row: &Vec<i64>| row.iter().all(|&a| a >= 0);
+        if self
+            .rows
+            .iter()
+            .zip(&self.rhs)
+            .any(|(row, &b)| b < 0 && all_non_negative(row))
+        {
+            return Ok(None);
+        }
+
+        let Some(reduced) = self.reduce()? else {
+            return Ok(None);
+        };
+        let bounds = reduced
+            .free
+            .iter()
+            .map(|&var| {
+                self.rows
+                    .iter()
+                    .zip(&self.rhs)
+                    .filter(|(row, _)| all_non_negative(row))
+                    .filter_map(|(row, &b)| {
+                        let a = *row.get(var)?;
+                        (a > 0).then(|| b.unsigned_abs() / a.unsigned_abs())
+                    })
+                    .min()
+                    .ok_or_else(|| anyhow!("free variable {var} has no upper bound"))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut search = Enumeration::new(&reduced, &bounds);
+        search.assign(0, 0);
+        Ok(search.best.map(|(_, x)| x))
+    }
+
+    /// Reduced row echelon form, or `None` if the equations contradict each other. Rows are kept
+    /// integral by scaling them rather than dividing, so the arithmetic stays exact.
+    fn reduce(&self) -> Result<Option<Reduced>> {
+        let overflow = || anyhow!("coefficients overflowed during elimination");
+        // Each row is the coefficients followed by the right hand side
+        let mut rows: Vec<Vec<i128>> = self
+            .rows
+            .iter()
+            .zip(&self.rhs)
+            .map(|(row, &b)| row.iter().chain([&b]).map(|&v| i128::from(v)).collect())
+            .collect();
+
+        let mut pivot_cols = Vec::new();
+        for col in 0..self.num_variables {
+            let next = pivot_cols.len();
+            let Some(found) = (next..rows.len()).find(|&r| entry(&rows, r, col) != 0) else {
+                continue;
+            };
+            rows.swap(next, found);
+            let pivot_row = rows.get(next).cloned().unwrap_or_default();
+            let p = entry(&rows, next, col);
+
+            for (r, row) in rows.iter_mut().enumerate() {
+                let c = row.get(col).copied().unwrap_or_default();
+                if r == next || c == 0 {
+                    continue;
+                }
+                for (x, &y) in row.iter_mut().zip(&pivot_row) {
+                    let scaled = p.checked_mul(*x).ok_or_else(overflow)?;
+                    *x = scaled
+                        .checked_sub(c.checked_mul(y).ok_or_else(overflow)?)
+                        .ok_or_else(overflow)?;
+                }
+                normalize(row);
+            }
+            pivot_cols.push(col);
+        }
+
+        // What's left below the pivots has no coefficients, so reads 0 = rhs
+        if rows
+            .iter()
+            .skip(pivot_cols.len())
+            .any(|row| row.last().is_some_and(|&b| b != 0))
+        {
+            return Ok(None);
+        }
+
+        let free: Vec<_> = (0..self.num_variables)
+            .filter(|col| !pivot_cols.contains(col))
+            .collect();
+        let pivots = pivot_cols
+            .iter()
+            .zip(&rows)
+            .map(|(&col, row)| {
+                // Flip the row so the pivot coefficient is positive
+                let sign = row.get(col).map_or(1, |p| p.signum());
+                PivotRow {
+                    col,
+                    coeff: sign * row.get(col).copied().unwrap_or(1),
+                    free_coeffs: free
+                        .iter()
+                        .map(|&f| sign * row.get(f).copied().unwrap_or_default())
+                        .collect(),
+                    rhs: sign * row.last().copied().unwrap_or_default(),
+                }
+            })
+            .collect();
+        Ok(Some(Reduced { pivots, free }))
+    }
+}
+
+/// Equation from the reduced system: `coeff * x[col] + sum(free_coeffs * free values) = rhs`
+#[derive(Debug)]
+struct PivotRow {
+    col: usize,
+    coeff: i128,
+    free_coeffs: Vec<i128>,
+    rhs: i128,
+}
+
+#[derive(Debug)]
+struct Reduced {
+    pivots: Vec<PivotRow>,
+    free: Vec<usize>,
+}
+
+/// Depth-first branch and bound over the free variables, each in increasing order of value.
+///
+/// Sums are compared scaled up by `scale`, a multiple of every pivot coefficient, so the sum a
+/// partial assignment would give with the pivots as they stand is an exact integer.
+struct Enumeration<'a> {
+    reduced: &'a Reduced,
+    bounds: &'a [u64],
+    values: Vec<u64>,
+    /// Right hand side of each pivot row less the free variables assigned so far
+    residuals: Vec<i128>,
+    scale: i128,
+    /// `slack[idx][r]` is the most the free variables from `idx` on can add to `residuals[r]`
+    slack: Vec<Vec<i128>>,
+    /// `floor[idx]` is the least the free variables from `idx` on can change the scaled sum by
+    floor: Vec<i128>,
+    /// Smallest sum found so far and the solution giving it
+    best: Option<(u64, Vec<u64>)>,
+}
+
+impl<'a> Enumeration<'a> {
+    fn new(reduced: &'a Reduced, bounds: &'a [u64]) -> Self {
+        let scale = reduced.pivots.iter().fold(1, |acc, p| lcm(acc, p.coeff));
+        let coeff = |p: &PivotRow, j: usize| p.free_coeffs.get(j).copied().unwrap_or_default();
+
+        let mut slack = vec![vec![0; reduced.pivots.len()]];
+        let mut floor = vec![0];
+        for (j, &bound) in bounds.iter().enumerate().rev() {
+            let bound = i128::from(bound);
+            // A unit of free variable j adds one to the sum, and shifts every pivot it appears in
+            let weight = scale
+                - reduced
+                    .pivots
+                    .iter()
+                    .map(|p| scale / p.coeff * coeff(p, j))
+                    .sum::<i128>();
+            let next_slack = slack
+                .last()
+                .into_iter()
+                .flatten()
+                .zip(&reduced.pivots)
+                .map(|(&s, p)| s + (-coeff(p, j)).max(0) * bound)
+                .collect();
+            let next_floor = floor.last().copied().unwrap_or_default() + weight.min(0) * bound;
+            slack.push(next_slack);
+            floor.push(next_floor);
+        }
+        slack.reverse();
+        floor.reverse();
+
+        Enumeration {
+            reduced,
+            bounds,
+            values: vec![0; bounds.len()],
+            residuals: reduced.pivots.iter().map(|p| p.rhs).collect(),
+            scale,
+            slack,
+            floor,
+            best: None,
+        }
+    }
+
+    fn assign(&mut self, idx: usize, free_sum: u64) {
+        let slack = self.slack.get(idx).map_or(&[][..], Vec::as_slice);
+        if self.residuals.iter().zip(slack).any(|(&r, &s)| r + s < 0) {
+            // Some pivot would have to go negative
+            return;
+        }
+        let scaled_sum = self.scale * i128::from(free_sum)
+            + self
+                .reduced
+                .pivots
+                .iter()
+                .zip(&self.residuals)
+                .map(|(p, &r)| self.scale / p.coeff * r)
+                .sum::<i128>();
+        let lowest = scaled_sum + self.floor.get(idx).copied().unwrap_or_default();
+        if self
+            .best
+            .as_ref()
+            .is_some_and(|(best, _)| lowest >= self.scale * i128::from(*best))
+        {
+            return;
+        }
+
+        let Some(&bound) = self.bounds.get(idx) else {
+            self.record(free_sum);
+            return;
+        };
+        let coeffs: Vec<_> = self
+            .reduced
+            .pivots
+            .iter()
+            .map(|p| p.free_coeffs.get(idx).copied().unwrap_or_default())
+            .collect();
+        for v in 0..=bound {
+            if let Some(slot) = self.values.get_mut(idx) {
+                *slot = v;
+            }
+            self.assign(idx + 1, free_sum + v);
+            for (r, c) in self.residuals.iter_mut().zip(&coeffs) {
+                *r -= c;
+            }
+        }
+        for (r, c) in self.residuals.iter_mut().zip(&coeffs) {
+            *r += c * (i128::from(bound) + 1);
+        }
+        if let Some(slot) = self.values.get_mut(idx) {
+            *slot = 0;
+        }
+    }
+
+    /// Keep the current assignment as the best so far, if every pivot comes out as a whole number.
+    /// The bound has already checked it beats the last best.
+    fn record(&mut self, free_sum: u64) {
+        let reduced = self.reduced;
+        let mut x = vec![0; reduced.pivots.len() + reduced.free.len()];
+        for (&var, &v) in reduced.free.iter().zip(&self.values) {
+            if let Some(slot) = x.get_mut(var) {
+                *slot = v;
+            }
+        }
+        let mut sum = free_sum;
+        for (pivot, &r) in reduced.pivots.iter().zip(&self.residuals) {
+            let Ok(v) = u64::try_from(r / pivot.coeff) else {
+                return;
+            };
+            if r % pivot.coeff != 0 {
+                return;
+            }
+            if let Some(slot) = x.get_mut(pivot.col) {
+                *slot = v;
+            }
+            sum += v;
+        }
+        self.best = Some((sum, x));
+    }
+}
+
+fn entry(rows: &[Vec<i128>], row: usize, col: usize) -> i128 {
+    rows.get(row)
+        .and_then(|r| r.get(col))
+        .copied()
+        .unwrap_or_default()
+}
+
+/// Divide a row through by the gcd of its entries, keeping them small
+fn normalize(row: &mut [i128]) {
+    let divisor = row.iter().fold(0, |acc, &v| gcd(acc, v.unsigned_abs()));
+    if divisor > 1 {
+        let divisor = i128::try_from(divisor).expect("gcd of i128 values fits in one");
+        for v in row {
+            *v /= divisor;
+        }
+    }
+}
+
+fn gcd(a: u128, b: u128) -> u128 {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+fn lcm(a: i128, b: i128) -> i128 {
+    let divisor = i128::try_from(gcd(a.unsigned_abs(), b.unsigned_abs())).unwrap_or(1);
+    (a / divisor * b).abs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use itertools::Itertools;
+
+    fn system(rows: &[&[i64]], rhs: &[i64]) -> LinearSystem {
+        LinearSystem::new(rows.iter().map(|r| r.to_vec()).collect(), rhs.to_vec())
+            .expect("well formed")
+    }
+
+    #[test]
+    fn test_free_variables() {
+        // Buttons (3) (1,3) (2) (2,3) (0,2) (0,1) reaching joltages {3,5,4,7}
+        let s = system(
+            &[
+                &[0, 0, 0, 0, 1, 1],
+                &[0, 1, 0, 0, 0, 1],
+                &[0, 0, 1, 1, 1, 0],
+                &[1, 1, 0, 1, 0, 0],
+            ],
+            &[3, 5, 4, 7],
+        );
+        let x = s.min_sum_solution().expect("bounded").expect("solvable");
+        assert!(s.is_solution(&x));
+        assert_eq!(10, x.iter().sum::<u64>());
+    }
+
+    #[test]
+    fn test_unique_solution() {
+        // Needs fractions along the way: 2x + y = 5, x + 3y = 5
+        let s = system(&[&[2, 1], &[1, 3]], &[5, 5]);
+        assert_eq!(
+            Some(vec![2, 1]),
+            s.min_sum_solution().expect("no free variables")
+        );
+    }
+
+    #[test]
+    fn test_no_solution() {
+        // Negative, fractional and contradictory
+        let negative = system(&[&[1, 1], &[1, -1]], &[1, 3]);
+        assert_eq!(
+            None,
+            negative.min_sum_solution().expect("no free variables")
+        );
+        let fractional = system(&[&[2]], &[3]);
+        assert_eq!(
+            None,
+            fractional.min_sum_solution().expect("no free variables")
+        );
+        let contradictory = system(&[&[1, 1], &[2, 2]], &[1, 3]);
+        assert_eq!(None, contradictory.min_sum_solution().expect("bounded"));
+    }
+
+    /// Smallest sum by trying everything up to `limit` for each variable
+    fn brute_force(s: &LinearSystem, limit: u64) -> Option<u64> {
+        (0..s.num_variables())
+            .map(|_| 0..=limit)
+            .multi_cartesian_product()
+            .filter(|x| s.is_solution(x))
+            .map(|x| x.iter().sum())
+            .min()
+    }
+
+    #[test]
+    fn test_matches_brute_force() {
+        // Every small system with coefficients up to 2 and right hand sides up to 4
+        let mut solved = 0;
+        for (equations, variables) in [(1, 2), (1, 3), (2, 2)] {
+            let ranges = (0..equations * variables)
+                .map(|_| 0..=2)
+                .chain((0..equations).map(|_| 0..=4));
+            for values in ranges.multi_cartesian_product() {
+                let (coefficients, rhs) = values.split_at(equations * variables);
+                let rows = coefficients
+                    .chunks(variables)
+                    .map(<[i64]>::to_vec)
+                    .collect();
+                let s = LinearSystem::new(rows, rhs.to_vec()).expect("well formed");
+                let Ok(found) = s.min_sum_solution() else {
+                    continue;
+                };
+                if let Some(x) = &found {
+                    assert!(s.is_solution(x), "{s:?} {x:?}");
+                    solved += 1;
+                }
+                let found = found.map(|x| x.iter().sum());
+                assert_eq!(brute_force(&s, 12), found, "{s:?}");
+            }
+        }
+        assert!(solved > 500, "only {solved} systems solved");
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(LinearSystem::new(vec![vec![1, 2], vec![3]], vec![1, 2]).is_err());
+        assert!(LinearSystem::new(vec![vec![1]], vec![]).is_err());
+        let unbounded = system(&[&[1, -1]], &[0]);
+        assert!(unbounded.min_sum_solution().is_err());
+    }
+}
@@ -0,0 +1,487 @@
+use anyhow::{Result, anyhow};
+
+const WORD_BITS: usize = u64::BITS as usize;
+
+/// Largest nullspace [`Solutions::min_weight`] will search. It tries every combination, so at the
+/// cap that's about 16 million steps, each an XOR over the whole vector.
+pub const MAX_SEARCH_NULLITY: usize = 24;
+
+/// Vector over GF(2), i.e. of bits added with XOR, packed 64 to a word. Bits past the end are
+/// always kept clear.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BitVector {
+    len: usize,
+    words: Vec<u64>,
+}
+
+impl BitVector {
+    /// All zeros
+    #[must_use]
+    pub fn new(len: usize) -> Self {
+        BitVector {
+            len,
+            words: vec![0; len.div_ceil(WORD_BITS)],
+        }
+    }
+
+    /// Vector of the given length with the listed bits set
+    /// # Errors
+    /// If a bit is out of range
+    pub fn from_ones<I: IntoIterator<Item = usize>>(len: usize, ones: I) -> Result<Self> {
+        let mut v = BitVector::new(len);
+        for i in ones {
+            v.set(i, true)?;
+        }
+        Ok(v)
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    #[must_use]
+    pub fn get(&self, i: usize) -> Option<bool> {
+        if i >= self.len {
+            return None;
+        }
+        let word = self.words.get(i / WORD_BITS)?;
+        Some(word & (1 << (i % WORD_BITS)) != 0)
+    }
+
+    /// # Errors
+    /// If `i` is out of range
+    pub fn set(&mut self, i: usize, val: bool) -> Result<()> {
+        let word = self.word_mut(i)?;
+        let mask = 1 << (i % WORD_BITS);
+        if val {
+            *word |= mask;
+        } else {
+            *word &= !mask;
+        }
+        Ok(())
+    }
+
+    /// # Errors
+    /// If `i` is out of range
+    pub fn flip(&mut self, i: usize) -> Result<()> {
+        *self.word_mut(i)? ^= 1 << (i % WORD_BITS);
+        Ok(())
+    }
+
+    #[must_use]
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    #[must_use]
+    pub fn is_zero(&self) -> bool {
+        self.words.iter().all(|&w| w == 0)
+    }
+
+    /// Indices of the set bits, in increasing order
+    pub fn ones(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(w, &word)| {
+            (0..WORD_BITS)
+                .filter(move |bit| word & (1 << bit) != 0)
+                .map(move |bit| w * WORD_BITS + bit)
+        })
+    }
+
+    /// Add `other` to this vector, bit by bit
+    /// # Errors
+    /// If the vectors differ in length
+    pub fn xor_assign(&mut self, other: &Self) -> Result<()> {
+        if self.len != other.len {
+            return Err(anyhow!(
+                "can't add vectors of length {} and {}",
+                self.len,
+                other.len
+            ));
+        }
+        for (a, b) in self.words.iter_mut().zip(&other.words) {
+            *a ^= b;
+        }
+        Ok(())
+    }
+
+    /// Dot product, i.e. whether an odd number of bits are set in both. Bits past the end of the
+    /// shorter vector are ignored.
+    #[must_use]
+    pub fn dot(&self, other: &Self) -> bool {
+        let ones: u32 = self
+            .words
+            .iter()
+            .zip(&other.words)
+            .map(|(a, b)| (a & b).count_ones())
+            .sum();
+        ones % 2 == 1
+    }
+
+    fn word_mut(&mut self, i: usize) -> Result<&mut u64> {
+        if i >= self.len {
+            return Err(anyhow!("bit {i} out of range for length {}", self.len));
+        }
+        self.words
+            .get_mut(i / WORD_BITS)
+            .ok_or_else(|| anyhow!("bit {i} out of range for length {}", self.len))
+    }
+}
+
+/// Matrix over GF(2), stored as a [`BitVector`] per row
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BitMatrix {
+    num_cols: usize,
+    rows: Vec<BitVector>,
+}
+
+impl BitMatrix {
+    /// All zeros
+    #[must_use]
+    pub fn new(num_rows: usize, num_cols: usize) -> Self {
+        BitMatrix {
+            num_cols,
+            rows: vec![BitVector::new(num_cols); num_rows],
+        }
+    }
+
+    /// Matrix with the given vectors as its columns, each `num_rows` long
+    /// # Errors
+    /// If a column isn't `num_rows` long
+    pub fn from_columns(num_rows: usize, columns: &[BitVector]) -> Result<Self> {
+        let mut m = BitMatrix::new(num_rows, columns.len());
+        for (c, column) in columns.iter().enumerate() {
+            if column.len() != num_rows {
+                return Err(anyhow!(
+                    "column {c} has length {}, expected {num_rows}",
+                    column.len()
+                ));
+            }
+            for r in column.ones() {
+                m.set(r, c, true)?;
+            }
+        }
+        Ok(m)
+    }
+
+    #[must_use]
+    pub fn num_rows(&self) -> usize {
+        self.rows.len()
+    }
+
+    #[must_use]
+    pub fn num_cols(&self) -> usize {
+        self.num_cols
+    }
+
+    #[must_use]
+    pub fn get(&self, row: usize, col: usize) -> Option<bool> {
+        self.rows.get(row)?.get(col)
+    }
+
+    /// # Errors
+    /// If the position is out of range
+    pub fn set(&mut self, row: usize, col: usize, val: bool) -> Result<()> {
+        self.rows
+            .get_mut(row)
+            .ok_or_else(|| anyhow!("row {row} out of range"))?
+            .set(col, val)
+    }
+
+    #[must_use]
+    pub fn row(&self, row: usize) -> Option<&BitVector> {
+        self.rows.get(row)
+    }
+
+    /// The product `self * x`
+    /// # Errors
+    /// If `x` isn't as long as a row
+    pub fn mul_vec(&self, x: &BitVector) -> Result<BitVector> {
+        if x.len() != self.num_cols {
+            return Err(anyhow!(
+                "vector of length {} can't multiply {} columns",
+                x.len(),
+                self.num_cols
+            ));
+        }
+        BitVector::from_ones(
+            self.num_rows(),
+            self.rows
+                .iter()
+                .enumerate()
+                .filter(|(_, row)| row.dot(x))
+                .map(|(r, _)| r),
+        )
+    }
+
+    /// Number of linearly independent rows, equally columns
+    #[must_use]
+    pub fn rank(&self) -> usize {
+        self.eliminate(&BitVector::new(self.num_rows()))
+            .pivots
+            .len()
+    }
+
+    /// Basis of the vectors `x` with `self * x = 0`
+    #[must_use]
+    pub fn nullspace(&self) -> Vec<BitVector> {
+        self.eliminate(&BitVector::new(self.num_rows())).nullspace()
+    }
+
+    /// Every `x` with `self * x = b`, or `None` if there are none
+    /// # Errors
+    /// If `b` isn't as long as a column
+    pub fn solve(&self, b: &BitVector) -> Result<Option<Solutions>> {
+        if b.len() != self.num_rows() {
+            return Err(anyhow!(
+                "right hand side of length {} for {} rows",
+                b.len(),
+                self.num_rows()
+            ));
+        }
+        let echelon = self.eliminate(b);
+        if !echelon.is_consistent() {
+            return Ok(None);
+        }
+        Ok(Some(Solutions {
+            particular: echelon.particular(),
+            nullspace: echelon.nullspace(),
+        }))
+    }
+
+    /// Gauss-Jordan elimination of the matrix augmented with `b`
+    fn eliminate(&self, b: &BitVector) -> Echelon {
+        let mut rows = self.rows.clone();
+        let mut rhs: Vec<bool> = (0..b.len()).map(|i| b.get(i) == Some(true)).collect();
+        let mut pivots = Vec::new();
+
+        for col in 0..self.num_cols {
+            let next = pivots.len();
+            let Some(found) = (next..rows.len())
+                .find(|&r| rows.get(r).and_then(|row| row.get(col)) == Some(true))
+            else {
+                continue;
+            };
+            rows.swap(next, found);
+            rhs.swap(next, found);
+            let (Some(pivot_row), Some(&pivot_rhs)) = (rows.get(next).cloned(), rhs.get(next))
+            else {
+                unreachable!("the pivot row was just found");
+            };
+
+            for (r, (row, b)) in rows.iter_mut().zip(rhs.iter_mut()).enumerate() {
+                if r != next && row.get(col) == Some(true) {
+                    row.xor_assign(&pivot_row)
+                        .expect("rows all have the same length");
+                    *b ^= pivot_rhs;
+                }
+            }
+            pivots.push(col);
+        }
+
+        Echelon {
+            num_cols: self.num_cols,
+            rows,
+            rhs,
+            pivots,
+        }
+    }
+}
+
+/// Reduced row echelon form of an augmented matrix. Row `i` has its leading one in column
+/// `pivots[i]`, and that column is clear in every other row.
+struct Echelon {
+    num_cols: usize,
+    rows: Vec<BitVector>,
+    rhs: Vec<bool>,
+    pivots: Vec<usize>,
+}
+
+impl Echelon {
+    /// Rows past the pivots are all zero, so need a zero right hand side
+    fn is_consistent(&self) -> bool {
+        self.rhs.iter().skip(self.pivots.len()).all(|&b| !b)
+    }
+
+    /// The solution with every free variable zero
+    fn particular(&self) -> BitVector {
+        let ones = self
+            .pivots
+            .iter()
+            .zip(&self.rhs)
+            .filter(|(_, b)| **b)
+            .map(|(&col, _)| col);
+        BitVector::from_ones(self.num_cols, ones).expect("pivots are columns")
+    }
+
+    /// One vector per free variable, setting it and whichever pivots cancel it out
+    fn nullspace(&self) -> Vec<BitVector> {
+        (0..self.num_cols)
+            .filter(|col| !self.pivots.contains(col))
+            .map(|free| {
+                let pivots = self
+                    .pivots
+                    .iter()
+                    .zip(&self.rows)
+                    .filter(|(_, row)| row.get(free) == Some(true))
+                    .map(|(&col, _)| col);
+                BitVector::from_ones(self.num_cols, pivots.chain([free]))
+                    .expect("pivots are columns")
+            })
+            .collect()
+    }
+}
+
+/// The solutions of a system of equations: [`Solutions::particular`] plus any combination of the
+/// [`Solutions::nullspace`] basis
+#[derive(Debug, Clone)]
+pub struct Solutions {
+    particular: BitVector,
+    nullspace: Vec<BitVector>,
+}
+
+impl Solutions {
+    #[must_use]
+    pub fn particular(&self) -> &BitVector {
+        &self.particular
+    }
+
+    #[must_use]
+    pub fn nullspace(&self) -> &[BitVector] {
+        &self.nullspace
+    }
+
+    /// The solution with the fewest ones. Tries every combination of the nullspace basis, so
+    /// takes time exponential in its size. The length of the vectors only matters a word per 64
+    /// bits, but the nullspace can have at most [`MAX_SEARCH_NULLITY`] dimensions.
+    /// # Errors
+    /// If the nullspace has more than [`MAX_SEARCH_NULLITY`] dimensions
+    pub fn min_weight(&self) -> Result<BitVector> {
+        let nullity = self.nullspace.len();
+        if nullity > MAX_SEARCH_NULLITY {
+            return Err(anyhow!(
+                "nullspace of {nullity} dimensions is too big to search"
+            ));
+        }
+
+        // Step through the combinations in Gray code order, so each differs from the last by a
+        // single basis vector
+        let mut current = self.particular.clone();
+        let mut best = current.clone();
+        for step in 1..1_u64 << nullity {
+            let changed = step.trailing_zeros() as usize;
+            let basis = self.nullspace.get(changed).expect("below the nullity");
+            current
+                .xor_assign(basis)
+                .expect("solutions all have the same length");
+            if current.count_ones() < best.count_ones() {
+                best = current.clone();
+            }
+        }
+        Ok(best)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vector(bits: &str) -> BitVector {
+        BitVector::from_ones(
+            bits.len(),
+            bits.chars()
+                .enumerate()
+                .filter(|&(_, c)| c == '1')
+                .map(|(i, _)| i),
+        )
+        .expect("in range")
+    }
+
+    #[test]
+    fn test_bit_vector() {
+        let mut v = BitVector::new(130);
+        v.set(0, true).expect("in range");
+        v.flip(129).expect("in range");
+        v.flip(64).expect("in range");
+        v.flip(64).expect("in range");
+        assert!(v.set(130, true).is_err());
+        assert_eq!(vec![0, 129], v.ones().collect::<Vec<_>>());
+        assert_eq!(Some(true), v.get(129));
+        assert_eq!(None, v.get(130));
+
+        let w = BitVector::from_ones(130, [0, 5, 129]).expect("in range");
+        assert!(!v.dot(&w));
+        v.xor_assign(&w).expect("same length");
+        assert_eq!(vec![5], v.ones().collect::<Vec<_>>());
+        assert!(v.xor_assign(&BitVector::new(3)).is_err());
+    }
+
+    #[test]
+    fn test_rank_and_nullspace() {
+        // The third column is the sum of the first two
+        let columns = [
+            vector("1100"),
+            vector("0110"),
+            vector("1010"),
+            vector("0001"),
+        ];
+        let m = BitMatrix::from_columns(4, &columns).expect("columns all length 4");
+        assert_eq!(3, m.rank());
+
+        let nullspace = m.nullspace();
+        assert_eq!(vec![vector("1110")], nullspace);
+        for v in &nullspace {
+            assert!(m.mul_vec(v).expect("right length").is_zero());
+        }
+    }
+
+    #[test]
+    fn test_solve() {
+        // Buttons (3) (1,3) (2) (2,3) (0,2) (0,1) with lights [.##.] as the goal
+        let columns = [
+            vector("0001"),
+            vector("0101"),
+            vector("0010"),
+            vector("0011"),
+            vector("1010"),
+            vector("1100"),
+        ];
+        let m = BitMatrix::from_columns(4, &columns).expect("columns all length 4");
+        let goal = vector("0110");
+        let solutions = m.solve(&goal).expect("right length").expect("solvable");
+        assert_eq!(2, solutions.nullspace().len());
+        assert_eq!(
+            goal,
+            m.mul_vec(solutions.particular()).expect("right length")
+        );
+
+        let best = solutions.min_weight().expect("small nullspace");
+        assert_eq!(2, best.count_ones());
+        assert_eq!(goal, m.mul_vec(&best).expect("right length"));
+    }
+
+    #[test]
+    fn test_no_solution() {
+        let m = BitMatrix::from_columns(2, &[vector("11")]).expect("column length 2");
+        assert!(m.solve(&vector("10")).expect("right length").is_none());
+        assert!(m.solve(&vector("101")).is_err());
+    }
+
+    #[test]
+    fn test_nullspace_too_big() {
+        // A single light toggled by every button leaves all but one button free
+        let columns = vec![vector("1"); MAX_SEARCH_NULLITY + 2];
+        let m = BitMatrix::from_columns(1, &columns).expect("column length 1");
+        let solutions = m
+            .solve(&vector("1"))
+            .expect("right length")
+            .expect("solvable");
+        assert_eq!(MAX_SEARCH_NULLITY + 1, solutions.nullspace().len());
+        assert!(solutions.min_weight().is_err());
+    }
+}
@@ -0,0 +1,265 @@
+use anyhow::{Result, anyhow};
+use itertools::Itertools;
+
+/// A simple polygon whose edges are all horizontal or vertical, stored on a compressed grid.
+///
+/// Only the distinct x and y values of the vertices are kept. Each value gets an even index in
+/// the compressed grid, and the open gap between two neighbouring values gets the odd index in
+/// between, so that
+/// ```text
+/// #.#      ##
+/// ...  and ##
+/// #.#
+/// ```
+/// stay distinguishable. Every compressed cell is either fully inside the polygon (interior or
+/// boundary) or fully outside it, which lets queries run on the compressed grid alone.
+#[derive(Debug)]
+pub struct RectilinearPolygon {
+    xs: Vec<u64>,
+    ys: Vec<u64>,
+    // Indexed [y][x], true for compressed cells inside or on the boundary
+    cells: Vec<Vec<bool>>,
+    // 2D prefix sums of `cells`: prefix[y][x] counts the inside cells above and left of (x, y)
+    prefix: Vec<Vec<usize>>,
+}
+
+impl RectilinearPolygon {
+    /// Build a polygon from its vertices given in order around the boundary as (x, y) pairs. The
+    /// last vertex connects back to the first.
+    /// # Errors
+    /// If there are fewer than four vertices, or two consecutive vertices are not on a horizontal
+    /// or vertical line
+    pub fn new<I>(vertices: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = (u64, u64)>,
+    {
+        let vertices: Vec<_> = vertices.into_iter().collect();
+        if vertices.len() < 4 {
+            return Err(anyhow!("polygon needs at least four vertices"));
+        }
+
+        let xs: Vec<_> = vertices.iter().map(|v| v.0).sorted().dedup().collect();
+        let ys: Vec<_> = vertices.iter().map(|v| v.1).sorted().dedup().collect();
+        let (width, height) = (2 * xs.len() - 1, 2 * ys.len() - 1);
+
+        // Rasterize the boundary. Separately track where vertical edges cross the odd rows, these
+        // are what flip between outside and inside when scanning along such a row.
+        let mut boundary = vec![vec![false; width]; height];
+        let mut crossings = vec![vec![false; width]; height];
+        for (a, b) in vertices.iter().circular_tuple_windows() {
+            let (ax, ay) = (compress(&xs, a.0), compress(&ys, a.1));
+            let (bx, by) = (compress(&xs, b.0), compress(&ys, b.1));
+            let (Some(ax), Some(ay), Some(bx), Some(by)) = (ax, ay, bx, by) else {
+                unreachable!("vertex coordinates are all in the compression");
+            };
+
+            if ax == bx {
+                for y in ay.min(by)..=ay.max(by) {
+                    mark(&mut boundary, ax, y);
+                    if !y.is_multiple_of(2) {
+                        mark(&mut crossings, ax, y);
+                    }
+                }
+            } else if ay == by {
+                for x in ax.min(bx)..=ax.max(bx) {
+                    mark(&mut boundary, x, ay);
+                }
+            } else {
+                return Err(anyhow!("edge {a:?} -> {b:?} is not horizontal or vertical"));
+            }
+        }
+
+        // Odd rows lie strictly between vertices, so a scanline parity pass is exact. Even rows
+        // can only be entered from above by crossing a horizontal edge, which is boundary anyway.
+        let mut cells: Vec<Vec<bool>> = Vec::with_capacity(height);
+        for (y, (boundary_row, crossing_row)) in boundary.iter().zip(&crossings).enumerate() {
+            let row = if y.is_multiple_of(2) {
+                let above = cells.last();
+                boundary_row
+                    .iter()
+                    .enumerate()
+                    .map(|(x, &on_boundary)| {
+                        on_boundary || above.and_then(|row| row.get(x)).is_some_and(|&b| b)
+                    })
+                    .collect()
+            } else {
+                let mut inside = false;
+                boundary_row
+                    .iter()
+                    .zip(crossing_row)
+                    .map(|(&on_boundary, &crosses)| {
+                        inside ^= crosses;
+                        on_boundary || inside
+                    })
+                    .collect()
+            };
+            cells.push(row);
+        }
+
+        let mut prefix = vec![vec![0; width + 1]];
+        for row in &cells {
+            let above = prefix.last().expect("prefix starts non-empty");
+            let mut row_sum = 0;
+            let next = std::iter::once(0)
+                .chain(row.iter().zip(above.iter().skip(1)).map(|(&cell, &above)| {
+                    row_sum += usize::from(cell);
+                    row_sum + above
+                }))
+                .collect();
+            prefix.push(next);
+        }
+
+        Ok(Self {
+            xs,
+            ys,
+            cells,
+            prefix,
+        })
+    }
+
+    /// Whether the point is inside the polygon or on its boundary
+    #[must_use]
+    pub fn contains(&self, point: (u64, u64)) -> bool {
+        let (Some(x), Some(y)) = (compress(&self.xs, point.0), compress(&self.ys, point.1)) else {
+            return false;
+        };
+        self.cells
+            .get(y)
+            .and_then(|row| row.get(x))
+            .is_some_and(|&b| b)
+    }
+
+    /// Whether the whole rectangle with the given opposite corners lies inside the polygon,
+    /// touching the boundary allowed. Runs in O(log n) for n vertices.
+    #[must_use]
+    pub fn contains_rectangle(&self, corner: (u64, u64), opposite: (u64, u64)) -> bool {
+        let compressed = (
+            compress(&self.xs, corner.0.min(opposite.0)),
+            compress(&self.xs, corner.0.max(opposite.0)),
+            compress(&self.ys, corner.1.min(opposite.1)),
+            compress(&self.ys, corner.1.max(opposite.1)),
+        );
+        let (Some(min_x), Some(max_x), Some(min_y), Some(max_y)) = compressed else {
+            return false;
+        };
+
+        let inside_count = self.prefix_at(max_x + 1, max_y + 1) + self.prefix_at(min_x, min_y)
+            - self.prefix_at(min_x, max_y + 1)
+            - self.prefix_at(max_x + 1, min_y);
+        inside_count == (max_x - min_x + 1) * (max_y - min_y + 1)
+    }
+
+    /// Number of integer points inside the polygon or on its boundary
+    #[must_use]
+    pub fn area(&self) -> u64 {
+        self.cells
+            .iter()
+            .enumerate()
+            .map(|(y, row)| {
+                let height = real_span(&self.ys, y);
+                row.iter()
+                    .enumerate()
+                    .filter(|&(_, &inside)| inside)
+                    .map(|(x, _)| real_span(&self.xs, x) * height)
+                    .sum::<u64>()
+            })
+            .sum()
+    }
+
+    fn prefix_at(&self, x: usize, y: usize) -> usize {
+        *self
+            .prefix
+            .get(y)
+            .and_then(|row| row.get(x))
+            .expect("compressed indices are in bounds")
+    }
+}
+
+/// Compressed index of a real coordinate: even for one of the given values, odd for a gap
+/// between two of them, and `None` outside their range.
+fn compress(values: &[u64], v: u64) -> Option<usize> {
+    match values.binary_search(&v) {
+        Ok(idx) => Some(2 * idx),
+        Err(idx) if idx == 0 || idx == values.len() => None,
+        Err(idx) => Some(2 * idx - 1),
+    }
+}
+
+/// Number of integer coordinates a compressed index stands for
+fn real_span(values: &[u64], compressed: usize) -> u64 {
+    if compressed.is_multiple_of(2) {
+        return 1;
+    }
+    let idx = compressed / 2;
+    match (values.get(idx), values.get(idx + 1)) {
+        (Some(lo), Some(hi)) => hi - lo - 1,
+        _ => 0,
+    }
+}
+
+fn mark(grid: &mut [Vec<bool>], x: usize, y: usize) {
+    if let Some(cell) = grid.get_mut(y).and_then(|row| row.get_mut(x)) {
+        *cell = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Square with a notch cut into its left side
+    //   0123456
+    // 0 #XXXXX#
+    // 1 X.....X
+    // 2 #X#...X
+    // 3   X...X
+    // 4 #X#...X
+    // 5 X.....X
+    // 6 #XXXXX#
+    const NOTCHED: [(u64, u64); 8] = [
+        (0, 0),
+        (6, 0),
+        (6, 6),
+        (0, 6),
+        (0, 4),
+        (2, 4),
+        (2, 2),
+        (0, 2),
+    ];
+
+    #[test]
+    fn test_new() {
+        assert!(RectilinearPolygon::new(NOTCHED).is_ok());
+        assert!(RectilinearPolygon::new([(0, 0), (1, 0), (1, 1)]).is_err());
+        assert!(RectilinearPolygon::new([(0, 0), (1, 0), (2, 2), (0, 1)]).is_err());
+    }
+
+    #[test]
+    fn test_contains() {
+        let polygon = RectilinearPolygon::new(NOTCHED).expect("valid polygon");
+        assert!(polygon.contains((0, 0)));
+        assert!(polygon.contains((3, 3)));
+        assert!(polygon.contains((2, 3)));
+        assert!(polygon.contains((1, 5)));
+        assert!(!polygon.contains((1, 3)));
+        assert!(!polygon.contains((0, 3)));
+        assert!(!polygon.contains((7, 3)));
+    }
+
+    #[test]
+    fn test_contains_rectangle() {
+        let polygon = RectilinearPolygon::new(NOTCHED).expect("valid polygon");
+        assert!(polygon.contains_rectangle((0, 0), (6, 2)));
+        assert!(polygon.contains_rectangle((6, 6), (2, 0)));
+        assert!(polygon.contains_rectangle((3, 1), (4, 5)));
+        assert!(!polygon.contains_rectangle((0, 0), (6, 6)));
+        assert!(!polygon.contains_rectangle((1, 1), (3, 3)));
+        assert!(!polygon.contains_rectangle((5, 5), (7, 7)));
+    }
+
+    #[test]
+    fn test_area() {
+        let polygon = RectilinearPolygon::new(NOTCHED).expect("valid polygon");
+        assert_eq!(47, polygon.area());
+    }
+}
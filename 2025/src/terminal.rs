@@ -0,0 +1,401 @@
+use crate::grids::Grid;
+use crate::visualize::Rgb;
+use anyhow::Result;
+use std::fmt;
+use std::io::{BufRead, BufReader, Write};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const CLEAR_SCREEN: &str = "\x1b[2J";
+const CURSOR_HOME: &str = "\x1b[H";
+const CLEAR_LINE: &str = "\x1b[K";
+const CLEAR_BELOW: &str = "\x1b[J";
+const REVERSE: &str = "\x1b[7m";
+const RESET: &str = "\x1b[0m";
+
+/// How a single grid cell is drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cell {
+    pub glyph: char,
+    pub color: Option<Rgb>,
+}
+
+impl Cell {
+    #[must_use]
+    pub fn plain(glyph: char) -> Self {
+        Self { glyph, color: None }
+    }
+
+    #[must_use]
+    pub fn colored(glyph: char, color: Rgb) -> Self {
+        Self {
+            glyph,
+            color: Some(color),
+        }
+    }
+}
+
+/// Where a [`TerminalViewer`] gets the time from when pacing frames
+pub trait Clock {
+    fn now(&self) -> Instant;
+    fn sleep(&mut self, duration: Duration);
+}
+
+/// The real clock
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn sleep(&mut self, duration: Duration) {
+        thread::sleep(duration);
+    }
+}
+
+enum Sink {
+    Terminal {
+        out: Box<dyn Write>,
+        keys: Box<dyn BufRead>,
+        clock: Box<dyn Clock>,
+    },
+    Headless(Vec<String>),
+}
+
+impl fmt::Debug for Sink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Terminal { .. } => f.write_str("Terminal"),
+            Self::Headless(frames) => f.debug_tuple("Headless").field(frames).finish(),
+        }
+    }
+}
+
+/// Draws successive states of a grid simulation in place in the terminal, using ANSI escapes to
+/// move the cursor and color cells.
+///
+/// A headless viewer renders the same frames but keeps them as strings instead of printing them,
+/// and never sleeps or waits for input, so simulations can be checked in tests.
+#[derive(Debug)]
+pub struct TerminalViewer {
+    sink: Sink,
+    frame_interval: Option<Duration>,
+    step_mode: bool,
+    highlight_changes: bool,
+    previous: Option<Vec<Vec<Cell>>>,
+    last_drawn: Option<Instant>,
+}
+
+impl TerminalViewer {
+    /// Viewer drawing to stdout
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_io(
+            std::io::stdout(),
+            BufReader::new(std::io::stdin()),
+            SystemClock,
+        )
+    }
+
+    /// Viewer drawing to `out`, in step mode reading a line from `keys` after each frame, and
+    /// keeping to [`TerminalViewer::max_fps`] by `clock`
+    #[must_use]
+    pub fn with_io<W, R, C>(out: W, keys: R, clock: C) -> Self
+    where
+        W: Write + 'static,
+        R: BufRead + 'static,
+        C: Clock + 'static,
+    {
+        Self::with_sink(Sink::Terminal {
+            out: Box::new(out),
+            keys: Box::new(keys),
+            clock: Box::new(clock),
+        })
+    }
+
+    /// Viewer capturing frames in memory, see [`TerminalViewer::frames`]
+    #[must_use]
+    pub fn headless() -> Self {
+        Self::with_sink(Sink::Headless(Vec::new()))
+    }
+
+    fn with_sink(sink: Sink) -> Self {
+        Self {
+            sink,
+            frame_interval: None,
+            step_mode: false,
+            highlight_changes: true,
+            previous: None,
+            last_drawn: None,
+        }
+    }
+
+    /// Draw at most `fps` frames per second, sleeping between frames as needed
+    #[must_use]
+    pub fn max_fps(mut self, fps: u32) -> Self {
+        self.frame_interval = (fps > 0).then(|| Duration::from_secs(1) / fps);
+        self
+    }
+
+    /// Wait for Enter to be pressed after each frame
+    #[must_use]
+    pub fn step_mode(mut self, step_mode: bool) -> Self {
+        self.step_mode = step_mode;
+        self
+    }
+
+    /// Draw cells that changed since the previous frame in reverse video. On by default.
+    #[must_use]
+    pub fn highlight_changes(mut self, highlight_changes: bool) -> Self {
+        self.highlight_changes = highlight_changes;
+        self
+    }
+
+    /// Frames captured so far. Always empty unless the viewer is headless.
+    #[must_use]
+    pub fn frames(&self) -> &[String] {
+        match &self.sink {
+            Sink::Terminal { .. } => &[],
+            Sink::Headless(frames) => frames,
+        }
+    }
+
+    /// Draw the next frame, styling each cell with `style`
+    pub fn draw<T, F>(&mut self, grid: &Grid<T>, style: F) -> Result<()>
+    where
+        F: Fn(&T) -> Cell,
+    {
+        let mut cells: Vec<Vec<Cell>> = vec![Vec::new(); grid.num_rows()];
+        for pos in grid.all_positions() {
+            if let (Some(row), Some(val)) = (cells.get_mut(pos.row()), grid.get(&pos)) {
+                row.push(style(val));
+            }
+        }
+        let frame = self.render(&cells);
+        let first_frame = self.previous.replace(cells).is_none();
+
+        match &mut self.sink {
+            Sink::Headless(frames) => frames.push(frame),
+            Sink::Terminal { out, keys, clock } => {
+                if let (Some(interval), Some(last)) = (self.frame_interval, self.last_drawn) {
+                    let elapsed = clock.now().saturating_duration_since(last);
+                    clock.sleep(interval.saturating_sub(elapsed));
+                }
+                self.last_drawn = Some(clock.now());
+
+                if first_frame {
+                    write!(out, "{CLEAR_SCREEN}")?;
+                }
+                // Clear whatever a bigger previous frame or the step prompt left behind
+                let frame = frame.replace('\n', &format!("{CLEAR_LINE}\n"));
+                write!(out, "{CURSOR_HOME}{frame}{CLEAR_BELOW}")?;
+                out.flush()?;
+
+                if self.step_mode {
+                    writeln!(out, "-- press Enter for the next step --")?;
+                    out.flush()?;
+                    keys.read_line(&mut String::new())?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn render(&self, cells: &[Vec<Cell>]) -> String {
+        let mut frame = String::new();
+        for (r, row) in cells.iter().enumerate() {
+            for (c, cell) in row.iter().enumerate() {
+                let changed = self.highlight_changes
+                    && self.previous.as_ref().is_some_and(|prev| {
+                        prev.get(r).and_then(|prev_row| prev_row.get(c)) != Some(cell)
+                    });
+                if changed {
+                    frame.push_str(REVERSE);
+                }
+                if let Some(Rgb(red, green, blue)) = cell.color {
+                    frame.push_str(&format!("\x1b[38;2;{red};{green};{blue}m"));
+                }
+                frame.push(cell.glyph);
+                if changed || cell.color.is_some() {
+                    frame.push_str(RESET);
+                }
+            }
+            frame.push('\n');
+        }
+        frame
+    }
+}
+
+impl Default for TerminalViewer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grids::Position;
+    use std::cell::RefCell;
+    use std::io::Cursor;
+    use std::rc::Rc;
+
+    /// Output that can still be read after the viewer takes it
+    #[derive(Clone, Default)]
+    struct Shared(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Shared {
+        fn text(&self) -> String {
+            String::from_utf8(self.0.borrow().clone()).expect("utf-8")
+        }
+    }
+
+    fn style(on: &bool) -> Cell {
+        Cell::plain(if *on { '#' } else { '.' })
+    }
+
+    #[test]
+    fn test_headless_frames() {
+        let mut viewer = TerminalViewer::headless().highlight_changes(false);
+        let mut grid = Grid::new(vec![
+            vec![true, false], // force multi-line format
+            vec![false, false],
+        ]);
+        viewer.draw(&grid, style).expect("succeeds");
+        grid.set(&Position::new(1, 1), true)
+            .expect("valid position");
+        viewer.draw(&grid, style).expect("succeeds");
+
+        assert_eq!(vec!["#.\n..\n", "#.\n.#\n"], viewer.frames());
+    }
+
+    #[test]
+    fn test_highlight_changes() {
+        let mut viewer = TerminalViewer::headless();
+        let mut grid = Grid::new(vec![vec![false, false]]);
+        viewer.draw(&grid, style).expect("succeeds");
+        grid.set(&Position::new(0, 0), true)
+            .expect("valid position");
+        viewer.draw(&grid, style).expect("succeeds");
+
+        assert_eq!(vec!["..\n", "\x1b[7m#\x1b[0m.\n"], viewer.frames());
+    }
+
+    #[test]
+    fn test_colors() {
+        let mut viewer = TerminalViewer::headless();
+        let grid = Grid::new(vec![vec![1, 2]]);
+        viewer
+            .draw(&grid, |&v| {
+                if v == 1 {
+                    Cell::colored('a', Rgb(255, 0, 0))
+                } else {
+                    Cell::plain('b')
+                }
+            })
+            .expect("succeeds");
+
+        assert_eq!(vec!["\x1b[38;2;255;0;0ma\x1b[0mb\n"], viewer.frames());
+    }
+
+    #[test]
+    fn test_terminal_clears_leftovers() {
+        let out = Shared::default();
+        let mut viewer = TerminalViewer::with_io(out.clone(), Cursor::new(""), SystemClock);
+        viewer.highlight_changes = false;
+        viewer
+            .draw(&Grid::new(vec![vec![true, true], vec![true, true]]), style)
+            .expect("succeeds");
+        viewer
+            .draw(&Grid::new(vec![vec![false]]), style)
+            .expect("succeeds");
+
+        assert_eq!(
+            "\x1b[2J\x1b[H##\x1b[K\n##\x1b[K\n\x1b[J\x1b[H.\x1b[K\n\x1b[J",
+            out.text()
+        );
+    }
+
+    #[test]
+    fn test_step_mode() {
+        let out = Shared::default();
+        let keys = Cursor::new("\n\n");
+        let mut viewer = TerminalViewer::with_io(out.clone(), keys, SystemClock).step_mode(true);
+        let grid = Grid::new(vec![vec![true]]);
+        viewer.draw(&grid, style).expect("succeeds");
+        viewer.draw(&grid, style).expect("succeeds");
+
+        let text = out.text();
+        assert_eq!(2, text.matches("press Enter").count());
+        // Each frame clears the previous prompt
+        assert!(text.ends_with("\x1b[J-- press Enter for the next step --\n"));
+    }
+
+    /// Clock that only moves when told to, or when slept on
+    #[derive(Clone)]
+    struct FakeClock {
+        now: Rc<RefCell<Instant>>,
+        sleeps: Rc<RefCell<Vec<Duration>>>,
+    }
+
+    impl FakeClock {
+        fn new() -> Self {
+            Self {
+                now: Rc::new(RefCell::new(Instant::now())),
+                sleeps: Rc::default(),
+            }
+        }
+
+        fn advance(&self, duration: Duration) {
+            *self.now.borrow_mut() += duration;
+        }
+    }
+
+    impl Clock for FakeClock {
+        fn now(&self) -> Instant {
+            *self.now.borrow()
+        }
+
+        fn sleep(&mut self, duration: Duration) {
+            self.sleeps.borrow_mut().push(duration);
+            self.advance(duration);
+        }
+    }
+
+    #[test]
+    fn test_max_fps() {
+        let clock = FakeClock::new();
+        let mut viewer =
+            TerminalViewer::with_io(Shared::default(), Cursor::new(""), clock.clone()).max_fps(50);
+        let grid = Grid::new(vec![vec![true]]);
+        viewer.draw(&grid, style).expect("succeeds");
+        viewer.draw(&grid, style).expect("succeeds");
+        // Time spent between frames comes off the wait, and a late frame doesn't wait at all
+        clock.advance(Duration::from_millis(5));
+        viewer.draw(&grid, style).expect("succeeds");
+        clock.advance(Duration::from_millis(30));
+        viewer.draw(&grid, style).expect("succeeds");
+
+        let ms = Duration::from_millis;
+        assert_eq!(vec![ms(20), ms(15), ms(0)], *clock.sleeps.borrow());
+
+        let clock = FakeClock::new();
+        let mut unlimited =
+            TerminalViewer::with_io(Shared::default(), Cursor::new(""), clock.clone()).max_fps(0);
+        unlimited.draw(&grid, style).expect("succeeds");
+        unlimited.draw(&grid, style).expect("succeeds");
+        assert!(clock.sleeps.borrow().is_empty());
+    }
+}
@@ -0,0 +1,373 @@
+use crate::grids::Grid;
+use anyhow::{Context, Result, anyhow};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    pub const BLACK: Self = Self(0, 0, 0);
+    pub const WHITE: Self = Self(255, 255, 255);
+}
+
+/// An RGB image where each pixel is one cell of a puzzle grid
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<Rgb>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Ppm,
+    Png,
+}
+
+impl Format {
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Ppm => "ppm",
+            Self::Png => "png",
+        }
+    }
+}
+
+impl Image {
+    #[must_use]
+    pub fn new(width: usize, height: usize, background: Rgb) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![background; width * height],
+        }
+    }
+
+    /// Render a grid, coloring each cell with `color`. Cells missing from short rows are black.
+    pub fn from_grid<T, F>(grid: &Grid<T>, color: F) -> Self
+    where
+        F: Fn(&T) -> Rgb,
+    {
+        let mut image = Self::new(grid.num_cols(), grid.num_rows(), Rgb::BLACK);
+        for pos in grid.all_positions() {
+            if let Some(val) = grid.get(&pos) {
+                image.set(pos.row(), pos.col(), color(val));
+            }
+        }
+        image
+    }
+
+    /// Render sparse (row, col) cells, which may be negative. The image covers the bounding box of
+    /// the given cells and anything not given is `background`.
+    pub fn from_sparse<T, I, F>(cells: I, background: Rgb, color: F) -> Self
+    where
+        I: IntoIterator<Item = ((i64, i64), T)>,
+        F: Fn(&T) -> Rgb,
+    {
+        let cells: Vec<_> = cells.into_iter().collect();
+        let (Some(min_row), Some(max_row), Some(min_col), Some(max_col)) = (
+            cells.iter().map(|((r, _), _)| *r).min(),
+            cells.iter().map(|((r, _), _)| *r).max(),
+            cells.iter().map(|((_, c), _)| *c).min(),
+            cells.iter().map(|((_, c), _)| *c).max(),
+        ) else {
+            return Self::new(0, 0, background);
+        };
+
+        let height = usize::try_from(max_row - min_row + 1).expect("bounding box fits in memory");
+        let width = usize::try_from(max_col - min_col + 1).expect("bounding box fits in memory");
+        let mut image = Self::new(width, height, background);
+        for ((row, col), val) in &cells {
+            let row = usize::try_from(row - min_row).expect("row within bounding box");
+            let col = usize::try_from(col - min_col).expect("col within bounding box");
+            image.set(row, col, color(val));
+        }
+        image
+    }
+
+    #[must_use]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    #[must_use]
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    #[must_use]
+    pub fn get(&self, row: usize, col: usize) -> Option<Rgb> {
+        if col >= self.width {
+            return None;
+        }
+        self.pixels.get(row * self.width + col).copied()
+    }
+
+    /// Set a pixel, ignoring positions outside the image
+    pub fn set(&mut self, row: usize, col: usize, color: Rgb) {
+        if col >= self.width {
+            return;
+        }
+        if let Some(pixel) = self.pixels.get_mut(row * self.width + col) {
+            *pixel = color;
+        }
+    }
+
+    /// Blow each pixel up into a `factor` x `factor` square, since one pixel per cell is usually
+    /// too small to see
+    #[must_use]
+    pub fn scaled(&self, factor: usize) -> Self {
+        let mut pixels = Vec::with_capacity(self.pixels.len() * factor * factor);
+        for row in self.pixels.chunks(self.width.max(1)) {
+            let scaled_row: Vec<_> = row
+                .iter()
+                .flat_map(|&p| std::iter::repeat_n(p, factor))
+                .collect();
+            for _ in 0..factor {
+                pixels.extend_from_slice(&scaled_row);
+            }
+        }
+        Self {
+            width: self.width * factor,
+            height: self.height * factor,
+            pixels,
+        }
+    }
+
+    /// Write as a binary PPM (P6)
+    pub fn write_ppm<W: Write>(&self, mut w: W) -> Result<()> {
+        write!(w, "P6\n{} {}\n255\n", self.width, self.height)?;
+        w.write_all(&self.raw_bytes())?;
+        Ok(())
+    }
+
+    /// Write as an 8-bit RGB PNG. The image data is stored uncompressed, which keeps the encoder
+    /// small at the cost of file size.
+    pub fn write_png<W: Write>(&self, mut w: W) -> Result<()> {
+        let width = u32::try_from(self.width).context("image too wide for png")?;
+        let height = u32::try_from(self.height).context("image too tall for png")?;
+
+        w.write_all(b"\x89PNG\r\n\x1a\n")?;
+
+        let mut header = Vec::with_capacity(13);
+        header.extend_from_slice(&width.to_be_bytes());
+        header.extend_from_slice(&height.to_be_bytes());
+        // Bit depth 8, truecolor, default compression/filter methods, not interlaced
+        header.extend_from_slice(&[8, 2, 0, 0, 0]);
+        write_png_chunk(&mut w, b"IHDR", &header)?;
+
+        // Every scanline starts with its filter type, 0 meaning no filtering
+        let mut scanlines = Vec::with_capacity(self.height * (3 * self.width + 1));
+        for row in self.pixels.chunks(self.width.max(1)) {
+            scanlines.push(0);
+            scanlines.extend(row.iter().flat_map(|p| [p.0, p.1, p.2]));
+        }
+        write_png_chunk(&mut w, b"IDAT", &zlib_stored(&scanlines))?;
+        write_png_chunk(&mut w, b"IEND", &[])?;
+        Ok(())
+    }
+
+    /// Save to a file in the given format
+    pub fn save(&self, path: &Path, format: Format) -> Result<()> {
+        let file =
+            File::create(path).with_context(|| format!("creating {}", path.to_string_lossy()))?;
+        let mut w = BufWriter::new(file);
+        match format {
+            Format::Ppm => self.write_ppm(&mut w)?,
+            Format::Png => self.write_png(&mut w)?,
+        }
+        w.flush()?;
+        Ok(())
+    }
+
+    fn raw_bytes(&self) -> Vec<u8> {
+        self.pixels.iter().flat_map(|p| [p.0, p.1, p.2]).collect()
+    }
+}
+
+/// Writes the steps of a simulation as a numbered image sequence, `frame_00000.png` and so on,
+/// ready to be stitched together by e.g. `ffmpeg -i frame_%05d.png out.gif`
+#[derive(Debug)]
+pub struct FrameWriter {
+    dir: PathBuf,
+    format: Format,
+    scale: usize,
+    next_frame: usize,
+}
+
+impl FrameWriter {
+    /// Create a writer into `dir`, creating the directory if needed. Every frame is scaled up by
+    /// `scale` before being written.
+    pub fn new(dir: &Path, format: Format, scale: usize) -> Result<Self> {
+        if scale == 0 {
+            return Err(anyhow!("scale must be positive"));
+        }
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("creating {}", dir.to_string_lossy()))?;
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            format,
+            scale,
+            next_frame: 0,
+        })
+    }
+
+    /// Write the next frame, returning the path it was written to
+    pub fn write(&mut self, image: &Image) -> Result<PathBuf> {
+        let path = self.dir.join(format!(
+            "frame_{:05}.{}",
+            self.next_frame,
+            self.format.extension()
+        ));
+        image.scaled(self.scale).save(&path, self.format)?;
+        self.next_frame += 1;
+        Ok(path)
+    }
+
+    #[must_use]
+    pub fn frames_written(&self) -> usize {
+        self.next_frame
+    }
+}
+
+fn write_png_chunk<W: Write>(w: &mut W, kind: &[u8; 4], data: &[u8]) -> Result<()> {
+    let len = u32::try_from(data.len()).context("png chunk too large")?;
+    w.write_all(&len.to_be_bytes())?;
+    w.write_all(kind)?;
+    w.write_all(data)?;
+    let crc = crc32(kind.iter().chain(data));
+    w.write_all(&crc.to_be_bytes())?;
+    Ok(())
+}
+
+/// Wrap data in a zlib stream made of uncompressed deflate blocks
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    const MAX_BLOCK: usize = 65_535;
+
+    // CMF/FLG for deflate with a 32K window and no preset dictionary
+    let mut out = vec![0x78, 0x01];
+    let mut blocks = data.chunks(MAX_BLOCK).peekable();
+    if blocks.peek().is_none() {
+        out.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let is_final = blocks.peek().is_none();
+        let len = u16::try_from(block.len()).expect("blocks are at most MAX_BLOCK long");
+        out.push(u8::from(is_final));
+        out.extend_from_slice(&len.to_le_bytes());
+        out.extend_from_slice(&(!len).to_le_bytes());
+        out.extend_from_slice(block);
+    }
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
+
+fn crc32<'a, I: IntoIterator<Item = &'a u8>>(bytes: I) -> u32 {
+    let mut crc = 0xffff_ffff_u32;
+    for &b in bytes {
+        crc ^= u32::from(b);
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xedb8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    const MOD: u32 = 65_521;
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in bytes {
+        a = (a + u32::from(byte)) % MOD;
+        b = (b + a) % MOD;
+    }
+    (b << 16) | a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn checkerboard() -> Grid<bool> {
+        Grid::new(vec![
+            vec![true, false, true], // force multi-line format
+            vec![false, true, false],
+        ])
+    }
+
+    fn color(on: &bool) -> Rgb {
+        if *on { Rgb::WHITE } else { Rgb::BLACK }
+    }
+
+    #[test]
+    fn test_from_grid() {
+        let image = Image::from_grid(&checkerboard(), color);
+        assert_eq!((3, 2), (image.width(), image.height()));
+        assert_eq!(Some(Rgb::WHITE), image.get(0, 0));
+        assert_eq!(Some(Rgb::BLACK), image.get(1, 2));
+        assert_eq!(None, image.get(0, 3));
+    }
+
+    #[test]
+    fn test_from_sparse() {
+        let cells = vec![((-1, -1), 'a'), ((1, 2), 'b')];
+        let red = Rgb(255, 0, 0);
+        let image = Image::from_sparse(cells, Rgb::BLACK, |_| red);
+        assert_eq!((4, 3), (image.width(), image.height()));
+        assert_eq!(Some(red), image.get(0, 0));
+        assert_eq!(Some(Rgb::BLACK), image.get(1, 1));
+        assert_eq!(Some(red), image.get(2, 3));
+    }
+
+    #[test]
+    fn test_scaled() {
+        let image = Image::from_grid(&checkerboard(), color).scaled(2);
+        assert_eq!((6, 4), (image.width(), image.height()));
+        assert_eq!(Some(Rgb::WHITE), image.get(1, 1));
+        assert_eq!(Some(Rgb::BLACK), image.get(1, 2));
+        assert_eq!(Some(Rgb::WHITE), image.get(3, 3));
+    }
+
+    #[test]
+    fn test_write_ppm() {
+        let mut out = Vec::new();
+        Image::new(2, 1, Rgb(1, 2, 3))
+            .write_ppm(&mut out)
+            .expect("succeeds");
+        assert_eq!(b"P6\n2 1\n255\n\x01\x02\x03\x01\x02\x03".to_vec(), out);
+    }
+
+    #[test]
+    fn test_write_png() {
+        let mut out = Vec::new();
+        Image::from_grid(&checkerboard(), color)
+            .write_png(&mut out)
+            .expect("succeeds");
+        assert!(out.starts_with(b"\x89PNG\r\n\x1a\n\x00\x00\x00\x0dIHDR"));
+        assert!(out.ends_with(b"IEND\xae\x42\x60\x82"));
+    }
+
+    #[test]
+    fn test_checksums() {
+        assert_eq!(0xcbf4_3926, crc32(b"123456789"));
+        assert_eq!(0x11e6_0398, adler32(b"Wikipedia"));
+    }
+
+    #[test]
+    fn test_frame_writer() {
+        let dir = std::env::temp_dir().join(format!("aoc-frames-{}", std::process::id()));
+        let mut frames = FrameWriter::new(&dir, Format::Ppm, 3).expect("succeeds");
+        let image = Image::from_grid(&checkerboard(), color);
+        let first = frames.write(&image).expect("succeeds");
+        let second = frames.write(&image).expect("succeeds");
+        assert_eq!(2, frames.frames_written());
+        assert!(first.ends_with("frame_00000.ppm"));
+        assert!(second.ends_with("frame_00001.ppm"));
+
+        let written = std::fs::read(&second).expect("frame exists");
+        assert!(written.starts_with(b"P6\n9 6\n255\n"));
+        std::fs::remove_dir_all(&dir).expect("cleanup succeeds");
+    }
+}
@@ -0,0 +1,303 @@
+use anyhow::{Result, anyhow};
+use itertools::Either;
+use std::collections::HashMap;
+use std::ops::Range;
+
+pub mod dot;
+pub mod scc;
+
+/// Assigns each distinct name an id, counting up from zero in the order names are first seen
+#[derive(Debug, Clone, Default)]
+pub struct Interner {
+    ids: HashMap<String, usize>,
+    names: Vec<String>,
+}
+
+impl Interner {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Id of `name`, assigning the next free one if it hasn't been seen before
+    pub fn intern(&mut self, name: &str) -> usize {
+        if let Some(&id) = self.ids.get(name) {
+            return id;
+        }
+        let id = self.names.len();
+        self.ids.insert(name.to_string(), id);
+        self.names.push(name.to_string());
+        id
+    }
+
+    #[must_use]
+    pub fn id(&self, name: &str) -> Option<usize> {
+        self.ids.get(name).copied()
+    }
+
+    #[must_use]
+    pub fn name(&self, id: usize) -> Option<&str> {
+        self.names.get(id).map(String::as_str)
+    }
+
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Directed,
+    /// Every edge can be followed both ways with the same weight
+    Undirected,
+}
+
+/// How a [`Graph`] stores its edges
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Storage {
+    /// An adjacency matrix, for small graphs where most pairs of nodes are connected
+    Dense,
+    /// A list of outgoing edges per node, for sparse graphs
+    AdjacencyList,
+}
+
+#[derive(Debug, Clone)]
+enum Edges<W> {
+    Dense(Vec<Vec<Option<W>>>),
+    List(Vec<Vec<(usize, W)>>),
+}
+
+/// Weighted graph with named nodes. Names are interned, so after building the graph everything
+/// can work with the node ids `0..num_nodes()`.
+#[derive(Debug, Clone)]
+pub struct Graph<W> {
+    nodes: Interner,
+    direction: Direction,
+    edges: Edges<W>,
+}
+
+impl<W> Graph<W> {
+    #[must_use]
+    pub fn new(direction: Direction, storage: Storage) -> Self {
+        let edges = match storage {
+            Storage::Dense => Edges::Dense(Vec::new()),
+            Storage::AdjacencyList => Edges::List(Vec::new()),
+        };
+        Self {
+            nodes: Interner::new(),
+            direction,
+            edges,
+        }
+    }
+
+    #[must_use]
+    pub const fn direction(&self) -> Direction {
+        self.direction
+    }
+
+    /// Id of the node called `name`, adding it with no edges if it isn't in the graph yet
+    pub fn add_node(&mut self, name: &str) -> usize {
+        let id = self.nodes.intern(name);
+        let num_nodes = self.nodes.len();
+        match &mut self.edges {
+            Edges::Dense(rows) => {
+                if rows.len() < num_nodes {
+                    for row in rows.iter_mut() {
+                        row.push(None);
+                    }
+                    rows.push(std::iter::repeat_with(|| None).take(num_nodes).collect());
+                }
+            }
+            Edges::List(adj) => {
+                if adj.len() < num_nodes {
+                    adj.push(Vec::new());
+                }
+            }
+        }
+        id
+    }
+
+    #[must_use]
+    pub fn id(&self, name: &str) -> Option<usize> {
+        self.nodes.id(name)
+    }
+
+    #[must_use]
+    pub fn name(&self, id: usize) -> Option<&str> {
+        self.nodes.name(id)
+    }
+
+    #[must_use]
+    pub const fn num_nodes(&self) -> usize {
+        self.nodes.len()
+    }
+
+    #[must_use]
+    pub const fn node_ids(&self) -> Range<usize> {
+        0..self.nodes.len()
+    }
+
+    /// Weight of the edge from `from` to `to`, if there is one
+    #[must_use]
+    pub fn weight(&self, from: usize, to: usize) -> Option<&W> {
+        match &self.edges {
+            Edges::Dense(rows) => rows.get(from)?.get(to)?.as_ref(),
+            Edges::List(adj) => adj
+                .get(from)?
+                .iter()
+                .find(|(id, _)| *id == to)
+                .map(|(_, w)| w),
+        }
+    }
+
+    /// Nodes reachable from `id` by a single edge, along with that edge's weight
+    pub fn neighbors(&self, id: usize) -> impl Iterator<Item = (usize, &W)> + '_ {
+        match &self.edges {
+            Edges::Dense(rows) => Either::Left(
+                rows.get(id)
+                    .into_iter()
+                    .flatten()
+                    .enumerate()
+                    .filter_map(|(to, w)| w.as_ref().map(|w| (to, w))),
+            ),
+            Edges::List(adj) => {
+                Either::Right(adj.get(id).into_iter().flatten().map(|(to, w)| (*to, w)))
+            }
+        }
+    }
+
+    /// Every edge as `(from, to, weight)`. Undirected edges are only listed once, with `from <= to`.
+    pub fn edges(&self) -> impl Iterator<Item = (usize, usize, &W)> + '_ {
+        self.node_ids().flat_map(move |from| {
+            self.neighbors(from)
+                .filter(move |&(to, _)| self.direction == Direction::Directed || from <= to)
+                .map(move |(to, w)| (from, to, w))
+        })
+    }
+
+    /// Set the edge from `from` to `to`, for ids already known to be nodes in the graph
+    fn set_one_way(&mut self, from: usize, to: usize, weight: W) {
+        match &mut self.edges {
+            Edges::Dense(rows) => {
+                if let Some(slot) = rows.get_mut(from).and_then(|row| row.get_mut(to)) {
+                    *slot = Some(weight);
+                }
+            }
+            Edges::List(adj) => {
+                let Some(out) = adj.get_mut(from) else {
+                    return;
+                };
+                if let Some(existing) = out.iter_mut().find(|(id, _)| *id == to) {
+                    existing.1 = weight;
+                } else {
+                    out.push((to, weight));
+                }
+            }
+        }
+    }
+}
+
+impl<W: Clone> Graph<W> {
+    /// Add an edge between the named nodes, adding the nodes too if needed. An existing edge
+    /// between them has its weight replaced. Returns the ids of both ends.
+    pub fn add_edge(&mut self, from: &str, to: &str, weight: W) -> (usize, usize) {
+        let (from_id, to_id) = (self.add_node(from), self.add_node(to));
+        self.link(from_id, to_id, weight);
+        (from_id, to_id)
+    }
+
+    /// Add an edge between two existing nodes, replacing any existing edge between them
+    /// # Errors
+    /// If either id is not a node in the graph
+    pub fn add_edge_by_id(&mut self, from: usize, to: usize, weight: W) -> Result<()> {
+        if from >= self.num_nodes() || to >= self.num_nodes() {
+            return Err(anyhow!("edge {from} -> {to} refers to an unknown node"));
+        }
+        self.link(from, to, weight);
+        Ok(())
+    }
+
+    /// [`Graph::add_edge_by_id`] for ids already known to be nodes in the graph
+    fn link(&mut self, from: usize, to: usize, weight: W) {
+        if self.direction == Direction::Undirected && from != to {
+            self.set_one_way(to, from, weight.clone());
+        }
+        self.set_one_way(from, to, weight);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn triangle(direction: Direction, storage: Storage) -> Graph<u32> {
+        let mut g = Graph::new(direction, storage);
+        g.add_edge("a", "b", 1);
+        g.add_edge("b", "c", 2);
+        g.add_edge("c", "a", 3);
+        g
+    }
+
+    #[test]
+    fn test_interner() {
+        let mut interner = Interner::new();
+        assert_eq!(0, interner.intern("x"));
+        assert_eq!(1, interner.intern("y"));
+        assert_eq!(0, interner.intern("x"));
+        assert_eq!(Some(1), interner.id("y"));
+        assert_eq!(None, interner.id("z"));
+        assert_eq!(Some("x"), interner.name(0));
+        assert_eq!(2, interner.len());
+    }
+
+    #[test]
+    fn test_directed() {
+        for storage in [Storage::Dense, Storage::AdjacencyList] {
+            let g = triangle(Direction::Directed, storage);
+            let (a, b) = (g.id("a").expect("a"), g.id("b").expect("b"));
+            assert_eq!(3, g.num_nodes());
+            assert_eq!(Some(&1), g.weight(a, b));
+            assert_eq!(None, g.weight(b, a));
+            assert_eq!(vec![(b, &1)], g.neighbors(a).collect::<Vec<_>>());
+            assert_eq!(3, g.edges().count());
+        }
+    }
+
+    #[test]
+    fn test_undirected() {
+        for storage in [Storage::Dense, Storage::AdjacencyList] {
+            let mut g = triangle(Direction::Undirected, storage);
+            let (a, c) = (g.id("a").expect("a"), g.id("c").expect("c"));
+            assert_eq!(Some(&3), g.weight(a, c));
+            assert_eq!(Some(&3), g.weight(c, a));
+            assert_eq!(2, g.neighbors(a).count());
+            assert_eq!(3, g.edges().count());
+
+            g.add_edge("a", "c", 10);
+            assert_eq!(Some(&10), g.weight(c, a));
+            assert_eq!(3, g.edges().count());
+        }
+    }
+
+    #[test]
+    fn test_add_edge_by_id() {
+        let mut g: Graph<()> = Graph::new(Direction::Directed, Storage::AdjacencyList);
+        let a = g.add_node("a");
+        let b = g.add_node("b");
+        assert_eq!(a, g.add_node("a"));
+        assert!(g.add_edge_by_id(a, b, ()).is_ok());
+        assert!(g.add_edge_by_id(a, 2, ()).is_err());
+        assert_eq!(
+            vec![b],
+            g.neighbors(a).map(|(id, ())| id).collect::<Vec<_>>()
+        );
+        assert_eq!(0, g.neighbors(b).count());
+        assert_eq!(0, g.neighbors(5).count());
+    }
+}
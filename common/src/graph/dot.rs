@@ -0,0 +1,182 @@
+use crate::graph::{Direction, Graph};
+use itertools::Itertools;
+use std::collections::HashSet;
+
+const HIGHLIGHTED_NODE: &str = "style=filled, fillcolor=gold";
+const HIGHLIGHTED_EDGE: &str = "color=red, penwidth=2";
+
+/// Builder for a Graphviz DOT document, for looking at a puzzle's structure with `dot -Tsvg`.
+/// Nodes are identified by name, which is quoted so any string will do.
+#[derive(Debug, Clone)]
+pub struct Dot {
+    direction: Direction,
+    statements: Vec<String>,
+}
+
+impl Dot {
+    #[must_use]
+    pub const fn new(direction: Direction) -> Self {
+        Self {
+            direction,
+            statements: Vec::new(),
+        }
+    }
+
+    /// Declare a node, shown with `label` in place of its name if given
+    pub fn node(&mut self, name: &str, label: Option<&str>, highlighted: bool) -> &mut Self {
+        let attrs = attributes(label, highlighted.then_some(HIGHLIGHTED_NODE));
+        self.statements.push(format!("{}{attrs}", quote(name)));
+        self
+    }
+
+    /// Add an edge, declaring either end that hasn't been declared already
+    pub fn edge(
+        &mut self,
+        from: &str,
+        to: &str,
+        label: Option<&str>,
+        highlighted: bool,
+    ) -> &mut Self {
+        let op = match self.direction {
+            Direction::Directed => "->",
+            Direction::Undirected => "--",
+        };
+        let attrs = attributes(label, highlighted.then_some(HIGHLIGHTED_EDGE));
+        self.statements
+            .push(format!("{} {op} {}{attrs}", quote(from), quote(to)));
+        self
+    }
+
+    #[must_use]
+    pub fn render(&self) -> String {
+        let kind = match self.direction {
+            Direction::Directed => "digraph",
+            Direction::Undirected => "graph",
+        };
+        let mut out = format!("{kind} {{\n");
+        for statement in &self.statements {
+            out.push_str("    ");
+            out.push_str(statement);
+            out.push_str(";\n");
+        }
+        out.push_str("}\n");
+        out
+    }
+}
+
+/// Nodes and edges to draw attention to when exporting a [`Graph`]
+#[derive(Debug, Clone, Default)]
+pub struct Highlight {
+    nodes: HashSet<usize>,
+    edges: HashSet<(usize, usize)>,
+}
+
+impl Highlight {
+    #[must_use]
+    pub fn none() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn nodes<I: IntoIterator<Item = usize>>(ids: I) -> Self {
+        Self {
+            nodes: ids.into_iter().collect(),
+            edges: HashSet::new(),
+        }
+    }
+
+    /// The nodes of a path and the edges between consecutive ones
+    #[must_use]
+    pub fn path(path: &[usize]) -> Self {
+        Self {
+            nodes: path.iter().copied().collect(),
+            edges: path.iter().copied().tuple_windows().collect(),
+        }
+    }
+
+    fn has_edge(&self, from: usize, to: usize, direction: Direction) -> bool {
+        self.edges.contains(&(from, to))
+            || (direction == Direction::Undirected && self.edges.contains(&(to, from)))
+    }
+}
+
+impl<W> Graph<W> {
+    /// The graph in DOT format. Edges are labeled with whatever `edge_label` gives for their
+    /// weight, or left bare when it gives `None`.
+    pub fn to_dot<F>(&self, highlight: &Highlight, edge_label: F) -> String
+    where
+        F: Fn(&W) -> Option<String>,
+    {
+        let name = |id| self.name(id).unwrap_or_default();
+        let mut dot = Dot::new(self.direction());
+        for id in self.node_ids() {
+            dot.node(name(id), None, highlight.nodes.contains(&id));
+        }
+        for (from, to, w) in self.edges() {
+            dot.edge(
+                name(from),
+                name(to),
+                edge_label(w).as_deref(),
+                highlight.has_edge(from, to, self.direction()),
+            );
+        }
+        dot.render()
+    }
+}
+
+fn attributes(label: Option<&str>, extra: Option<&str>) -> String {
+    let attrs: Vec<String> = label
+        .map(|l| format!("label={}", quote(l)))
+        .into_iter()
+        .chain(extra.map(str::to_string))
+        .collect();
+    if attrs.is_empty() {
+        String::new()
+    } else {
+        format!(" [{}]", attrs.join(", "))
+    }
+}
+
+/// DOT string literal, with line breaks turned into `\n` escapes
+fn quote(s: &str) -> String {
+    let escaped = s
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n");
+    format!("\"{escaped}\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::Storage;
+
+    #[test]
+    fn test_dot_builder() {
+        let mut dot = Dot::new(Direction::Directed);
+        dot.node("a", Some("say \"hi\"\nthere"), true)
+            .edge("a", "b", None, false)
+            .edge("b", "a", Some("7"), true);
+        assert_eq!(
+            "digraph {\n    \"a\" [label=\"say \\\"hi\\\"\\nthere\", style=filled, fillcolor=gold];\n    \"a\" -> \"b\";\n    \"b\" -> \"a\" [label=\"7\", color=red, penwidth=2];\n}\n",
+            dot.render()
+        );
+    }
+
+    #[test]
+    fn test_graph_to_dot() {
+        let mut g = Graph::new(Direction::Undirected, Storage::Dense);
+        g.add_edge("London", "Dublin", 464);
+        g.add_edge("Dublin", "Belfast", 141);
+        g.add_node("Paris");
+
+        let path = Highlight::path(&[2, 1]);
+        let dot = g.to_dot(&path, |w| Some(w.to_string()));
+        assert!(dot.starts_with("graph {\n"));
+        assert!(dot.contains("\"Paris\";"));
+        assert!(dot.contains("\"Dublin\" [style=filled, fillcolor=gold];"));
+        assert!(dot.contains("\"London\" -- \"Dublin\" [label=\"464\"];"));
+        assert!(dot.contains("\"Dublin\" -- \"Belfast\" [label=\"141\", color=red, penwidth=2];"));
+        assert_eq!(2, dot.matches(" -- ").count());
+    }
+}
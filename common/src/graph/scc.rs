@@ -0,0 +1,298 @@
+use crate::graph::{Direction, Graph, Storage};
+use anyhow::{Result, anyhow};
+
+/// Strongly connected components of a directed graph, by Tarjan's algorithm.
+///
+/// Components come in topological order, so every edge between two components goes from an
+/// earlier one to a later one, and each component lists its nodes in increasing order.
+#[must_use]
+pub fn strongly_connected_components<W>(graph: &Graph<W>) -> Vec<Vec<usize>> {
+    let mut tarjan = Tarjan::new(graph);
+    for root in graph.node_ids() {
+        if tarjan.index_of(root).is_none() {
+            tarjan.run(root);
+        }
+    }
+    // Tarjan finds a component only after everything it leads to
+    let mut components = tarjan.components;
+    components.reverse();
+    for component in &mut components {
+        component.sort_unstable();
+    }
+    components
+}
+
+/// Components that contain a cycle: those with more than one node, or a single node with an edge
+/// to itself
+#[must_use]
+pub fn cyclic_components<W>(graph: &Graph<W>) -> Vec<Vec<usize>> {
+    strongly_connected_components(graph)
+        .into_iter()
+        .filter(|c| match c.as_slice() {
+            [single] => graph.weight(*single, *single).is_some(),
+            _ => true,
+        })
+        .collect()
+}
+
+/// Check a directed graph has no cycles
+/// # Errors
+/// Naming the nodes of every cycle found, one group per strongly connected component
+pub fn ensure_acyclic<W>(graph: &Graph<W>) -> Result<()> {
+    let cycles = cyclic_components(graph);
+    if cycles.is_empty() {
+        return Ok(());
+    }
+    let groups: Vec<String> = cycles
+        .iter()
+        .map(|c| format!("[{}]", member_names(graph, c)))
+        .collect();
+    Err(anyhow!("graph has cycles through {}", groups.join(", ")))
+}
+
+/// A directed graph with each strongly connected component collapsed to a single node, which is
+/// always acyclic
+#[derive(Debug, Clone)]
+pub struct Condensation {
+    components: Vec<Vec<usize>>,
+    component_of: Vec<usize>,
+    dag: Graph<()>,
+}
+
+impl Condensation {
+    #[must_use]
+    pub fn new<W>(graph: &Graph<W>) -> Self {
+        let components = strongly_connected_components(graph);
+        let mut component_of = vec![0; graph.num_nodes()];
+        for (c, members) in components.iter().enumerate() {
+            for &id in members {
+                if let Some(slot) = component_of.get_mut(id) {
+                    *slot = c;
+                }
+            }
+        }
+
+        let mut dag = Graph::new(Direction::Directed, Storage::AdjacencyList);
+        for members in &components {
+            dag.add_node(&member_names(graph, members));
+        }
+        for (from, to, _) in graph.edges() {
+            let (Some(&a), Some(&b)) = (component_of.get(from), component_of.get(to)) else {
+                continue;
+            };
+            if a != b {
+                dag.link(a, b, ());
+            }
+        }
+
+        Self {
+            components,
+            component_of,
+            dag,
+        }
+    }
+
+    /// The components in topological order. Component `i` is node `i` of [`Condensation::dag`].
+    #[must_use]
+    pub fn components(&self) -> &[Vec<usize>] {
+        &self.components
+    }
+
+    /// Index of the component containing node `id` of the original graph
+    #[must_use]
+    pub fn component_of(&self, id: usize) -> Option<usize> {
+        self.component_of.get(id).copied()
+    }
+
+    /// One node per component, named by listing its members' names in order, with an edge
+    /// wherever the original graph has an edge between components
+    #[must_use]
+    pub const fn dag(&self) -> &Graph<()> {
+        &self.dag
+    }
+}
+
+/// Names of the given nodes in alphabetical order, so messages don't depend on interning order
+fn member_names<W>(graph: &Graph<W>, members: &[usize]) -> String {
+    let mut names: Vec<_> = members
+        .iter()
+        .map(|&id| graph.name(id).unwrap_or("?"))
+        .collect();
+    names.sort_unstable();
+    names.join(", ")
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Visit {
+    index: usize,
+    low_link: usize,
+    on_stack: bool,
+}
+
+/// State for an iterative Tarjan's algorithm, so deep graphs can't overflow the call stack
+struct Tarjan {
+    successors: Vec<Vec<usize>>,
+    visits: Vec<Option<Visit>>,
+    next_index: usize,
+    stack: Vec<usize>,
+    components: Vec<Vec<usize>>,
+}
+
+impl Tarjan {
+    fn new<W>(graph: &Graph<W>) -> Self {
+        Self {
+            successors: graph
+                .node_ids()
+                .map(|id| graph.neighbors(id).map(|(to, _)| to).collect())
+                .collect(),
+            visits: vec![None; graph.num_nodes()],
+            next_index: 0,
+            stack: Vec::new(),
+            components: Vec::new(),
+        }
+    }
+
+    fn index_of(&self, id: usize) -> Option<usize> {
+        self.visits.get(id).copied().flatten().map(|v| v.index)
+    }
+
+    fn visit_mut(&mut self, id: usize) -> &mut Visit {
+        self.visits
+            .get_mut(id)
+            .and_then(Option::as_mut)
+            .expect("only visited nodes are updated")
+    }
+
+    fn enter(&mut self, id: usize) {
+        if let Some(slot) = self.visits.get_mut(id) {
+            *slot = Some(Visit {
+                index: self.next_index,
+                low_link: self.next_index,
+                on_stack: true,
+            });
+        }
+        self.next_index += 1;
+        self.stack.push(id);
+    }
+
+    fn run(&mut self, root: usize) {
+        self.enter(root);
+        // Each frame is a node and how many of its successors have been looked at
+        let mut frames = vec![(root, 0)];
+        while let Some(&(node, child)) = frames.last() {
+            let next = self
+                .successors
+                .get(node)
+                .and_then(|s| s.get(child))
+                .copied();
+            if let Some(frame) = frames.last_mut() {
+                frame.1 += 1;
+            }
+
+            if let Some(next) = next {
+                match self.visits.get(next).copied().flatten() {
+                    None => {
+                        self.enter(next);
+                        frames.push((next, 0));
+                    }
+                    Some(visit) if visit.on_stack => {
+                        let v = self.visit_mut(node);
+                        v.low_link = v.low_link.min(visit.index);
+                    }
+                    Some(_) => {}
+                }
+                continue;
+            }
+
+            frames.pop();
+            let Visit {
+                index, low_link, ..
+            } = *self.visit_mut(node);
+            if let Some(&(parent, _)) = frames.last() {
+                let p = self.visit_mut(parent);
+                p.low_link = p.low_link.min(low_link);
+            }
+            if low_link == index {
+                self.pop_component(node);
+            }
+        }
+    }
+
+    fn pop_component(&mut self, root: usize) {
+        let mut component = Vec::new();
+        while let Some(id) = self.stack.pop() {
+            self.visit_mut(id).on_stack = false;
+            component.push(id);
+            if id == root {
+                break;
+            }
+        }
+        self.components.push(component);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph(edges: &[(&str, &str)]) -> Graph<()> {
+        let mut g = Graph::new(Direction::Directed, Storage::AdjacencyList);
+        for &(from, to) in edges {
+            g.add_edge(from, to, ());
+        }
+        g
+    }
+
+    fn names(g: &Graph<()>, components: &[Vec<usize>]) -> Vec<String> {
+        components.iter().map(|c| member_names(g, c)).collect()
+    }
+
+    #[test]
+    fn test_strongly_connected_components() {
+        let g = graph(&[
+            ("a", "b"),
+            ("b", "c"),
+            ("c", "a"),
+            ("c", "d"),
+            ("d", "e"),
+            ("e", "d"),
+            ("e", "f"),
+        ]);
+        assert_eq!(
+            vec!["a, b, c", "d, e", "f"],
+            names(&g, &strongly_connected_components(&g))
+        );
+        assert_eq!(vec!["a, b, c", "d, e"], names(&g, &cyclic_components(&g)));
+        assert_eq!(
+            "graph has cycles through [a, b, c], [d, e]",
+            ensure_acyclic(&g).expect_err("cyclic").to_string()
+        );
+    }
+
+    #[test]
+    fn test_acyclic() {
+        let g = graph(&[("a", "b"), ("a", "c"), ("b", "d"), ("c", "d")]);
+        assert!(ensure_acyclic(&g).is_ok());
+        assert_eq!(4, strongly_connected_components(&g).len());
+
+        let looped = graph(&[("a", "b"), ("b", "b")]);
+        assert_eq!(vec!["b"], names(&looped, &cyclic_components(&looped)));
+    }
+
+    #[test]
+    fn test_condensation() {
+        let g = graph(&[("x", "a"), ("a", "b"), ("b", "a"), ("b", "y"), ("a", "y")]);
+        let condensation = Condensation::new(&g);
+        let dag = condensation.dag();
+        assert_eq!(3, dag.num_nodes());
+        assert_eq!(2, dag.edges().count());
+        assert!(ensure_acyclic(dag).is_ok());
+
+        let a = g.id("a").expect("exists");
+        let b = g.id("b").expect("exists");
+        assert_eq!(condensation.component_of(a), condensation.component_of(b));
+        let ab = condensation.component_of(a).expect("in range");
+        assert_eq!(Some("a, b"), dag.name(ab));
+        assert_eq!(Some(&vec![a, b]), condensation.components().get(ab));
+    }
+}
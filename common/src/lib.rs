@@ -0,0 +1,3 @@
+//! Code shared between the years
+pub mod graph;
+pub mod search;
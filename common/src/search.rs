@@ -0,0 +1,380 @@
+use std::cmp::Reverse;
+use std::collections::hash_map::Entry;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+
+/// A state in a puzzle's search space, for use with [`bfs`], [`dijkstra`], [`astar`] and
+/// [`ida_star`]
+pub trait SearchState: Sized {
+    /// What gets from one state to the next, e.g. a spell cast or a button pushed
+    type Move: Clone;
+    /// Identifies equivalent states, see [`SearchState::key`]
+    type Key: Eq + Hash;
+
+    /// States reachable in one move, along with the move and its cost
+    fn successors(&self) -> impl Iterator<Item = (Self::Move, u64, Self)>;
+
+    fn is_goal(&self) -> bool;
+
+    /// Lower bound on the cost left to reach a goal. Must never overestimate for [`astar`] and
+    /// [`ida_star`] to find the cheapest path. Zero unless overridden.
+    fn heuristic(&self) -> u64 {
+        0
+    }
+
+    /// Key under which to remember having reached this state, so it isn't explored again. With
+    /// the default of `None` nothing is remembered, which suits spaces with no repeated states.
+    fn key(&self) -> Option<Self::Key> {
+        None
+    }
+}
+
+/// Cheapest way found to a goal
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Solution<M> {
+    cost: u64,
+    moves: Vec<M>,
+}
+
+impl<M> Solution<M> {
+    /// Total cost of the moves
+    #[must_use]
+    pub const fn cost(&self) -> u64 {
+        self.cost
+    }
+
+    /// Moves from the start to the goal, in order
+    #[must_use]
+    pub fn moves(&self) -> &[M] {
+        &self.moves
+    }
+}
+
+/// How much work a search did
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SearchStats {
+    /// States whose successors were generated
+    pub expanded: usize,
+    /// Successor states generated, including ones thrown away as repeats
+    pub generated: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct Outcome<M> {
+    pub solution: Option<Solution<M>>,
+    pub stats: SearchStats,
+}
+
+/// How each reached state was reached, so the moves to any of them can be recovered
+struct Trail<M> {
+    steps: Vec<(Option<usize>, Option<M>)>,
+}
+
+impl<M: Clone> Trail<M> {
+    const fn new() -> Self {
+        Self { steps: Vec::new() }
+    }
+
+    fn push(&mut self, parent: Option<usize>, m: Option<M>) -> usize {
+        self.steps.push((parent, m));
+        self.steps.len() - 1
+    }
+
+    fn moves_to(&self, mut idx: usize) -> Vec<M> {
+        let mut moves = Vec::new();
+        while let Some((parent, m)) = self.steps.get(idx) {
+            moves.extend(m.iter().cloned());
+            match parent {
+                Some(p) => idx = *p,
+                None => break,
+            }
+        }
+        moves.reverse();
+        moves
+    }
+}
+
+/// Breadth-first search, finding the goal reachable in the fewest moves. Move costs are added up
+/// for the solution's cost but otherwise ignored.
+pub fn bfs<S: SearchState>(start: S) -> Outcome<S::Move> {
+    let mut work = SearchStats::default();
+    let mut trail = Trail::new();
+    let mut seen = HashSet::new();
+    if let Some(key) = start.key() {
+        seen.insert(key);
+    }
+    let mut queue = VecDeque::from([(start, 0, trail.push(None, None))]);
+
+    while let Some((state, cost, idx)) = queue.pop_front() {
+        if state.is_goal() {
+            let moves = trail.moves_to(idx);
+            return Outcome {
+                solution: Some(Solution { cost, moves }),
+                stats: work,
+            };
+        }
+
+        work.expanded += 1;
+        for (m, step, next) in state.successors() {
+            work.generated += 1;
+            if next.key().is_some_and(|key| !seen.insert(key)) {
+                continue;
+            }
+            let next_idx = trail.push(Some(idx), Some(m));
+            queue.push_back((next, cost + step, next_idx));
+        }
+    }
+    Outcome {
+        solution: None,
+        stats: work,
+    }
+}
+
+/// Dijkstra's algorithm, finding the cheapest goal
+pub fn dijkstra<S: SearchState>(start: S) -> Outcome<S::Move> {
+    best_first(start, false)
+}
+
+/// A* search, finding the cheapest goal as long as [`SearchState::heuristic`] never
+/// overestimates
+pub fn astar<S: SearchState>(start: S) -> Outcome<S::Move> {
+    best_first(start, true)
+}
+
+fn best_first<S: SearchState>(start: S, use_heuristic: bool) -> Outcome<S::Move> {
+    let estimate = |s: &S| if use_heuristic { s.heuristic() } else { 0 };
+    let mut work = SearchStats::default();
+    let mut trail = Trail::new();
+    let mut best_costs = HashMap::new();
+    if let Some(key) = start.key() {
+        best_costs.insert(key, 0);
+    }
+
+    // States wait here until popped, the heap only holds their index
+    let mut pending = vec![];
+    let mut heap = BinaryHeap::new();
+    let start_idx = trail.push(None, None);
+    heap.push(Reverse((estimate(&start), 0, start_idx)));
+    pending.push(Some(start));
+
+    while let Some(Reverse((_, cost, idx))) = heap.pop() {
+        let Some(state) = pending.get_mut(idx).and_then(Option::take) else {
+            continue;
+        };
+        if state
+            .key()
+            .and_then(|key| best_costs.get(&key))
+            .is_some_and(|&best| best < cost)
+        {
+            // Stale entry, a cheaper way here was found after this one was queued
+            continue;
+        }
+        if state.is_goal() {
+            let moves = trail.moves_to(idx);
+            return Outcome {
+                solution: Some(Solution { cost, moves }),
+                stats: work,
+            };
+        }
+
+        work.expanded += 1;
+        for (m, step, next) in state.successors() {
+            work.generated += 1;
+            let next_cost = cost + step;
+            if let Some(key) = next.key() {
+                match best_costs.entry(key) {
+                    Entry::Occupied(mut e) => {
+                        if *e.get() <= next_cost {
+                            continue;
+                        }
+                        e.insert(next_cost);
+                    }
+                    Entry::Vacant(e) => {
+                        e.insert(next_cost);
+                    }
+                }
+            }
+            let next_idx = trail.push(Some(idx), Some(m));
+            heap.push(Reverse((next_cost + estimate(&next), next_cost, next_idx)));
+            pending.push(Some(next));
+        }
+    }
+    Outcome {
+        solution: None,
+        stats: work,
+    }
+}
+
+/// Iterative deepening A*, finding the cheapest goal as long as [`SearchState::heuristic`] never
+/// overestimates.
+///
+/// Runs repeated depth-first searches, each allowed to go a little further past the heuristic's
+/// estimate than the last. Uses next to no memory, at the cost of exploring shallow states again
+/// each round. States are only checked for repeats along the current path. Unlike the other
+/// searches nothing is queued, so `start` is only borrowed.
+pub fn ida_star<S: SearchState>(start: &S) -> Outcome<S::Move> {
+    let mut work = SearchStats::default();
+    let mut bound = start.heuristic();
+    let mut moves = Vec::new();
+    let mut path_keys = Vec::new();
+    if let Some(key) = start.key() {
+        path_keys.push(key);
+    }
+
+    loop {
+        match deepen(start, 0, bound, &mut moves, &mut path_keys, &mut work) {
+            Deepen::Found(cost) => {
+                return Outcome {
+                    solution: Some(Solution { cost, moves }),
+                    stats: work,
+                };
+            }
+            Deepen::Exceeded(next_bound) => bound = next_bound,
+            Deepen::Exhausted => {
+                return Outcome {
+                    solution: None,
+                    stats: work,
+                };
+            }
+        }
+    }
+}
+
+enum Deepen {
+    Found(u64),
+    /// Nothing found within the bound, but going as far as this might
+    Exceeded(u64),
+    Exhausted,
+}
+
+fn deepen<S: SearchState>(
+    state: &S,
+    cost: u64,
+    bound: u64,
+    moves: &mut Vec<S::Move>,
+    path_keys: &mut Vec<S::Key>,
+    work: &mut SearchStats,
+) -> Deepen {
+    let f = cost + state.heuristic();
+    if f > bound {
+        return Deepen::Exceeded(f);
+    }
+    if state.is_goal() {
+        return Deepen::Found(cost);
+    }
+
+    work.expanded += 1;
+    let mut next_bound = None;
+    for (m, step, next) in state.successors() {
+        work.generated += 1;
+        let key = next.key();
+        if key.as_ref().is_some_and(|k| path_keys.contains(k)) {
+            continue;
+        }
+        let has_key = key.is_some();
+        path_keys.extend(key);
+        moves.push(m);
+        let result = deepen(&next, cost + step, bound, moves, path_keys, work);
+        if let Deepen::Found(_) = result {
+            return result;
+        }
+        moves.pop();
+        if has_key {
+            path_keys.pop();
+        }
+        if let Deepen::Exceeded(f) = result {
+            next_bound = Some(next_bound.map_or(f, |b: u64| b.min(f)));
+        }
+    }
+    next_bound.map_or(Deepen::Exhausted, Deepen::Exceeded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Walking along a number line towards 10, either a step at a time for 3 or a jump of 4 for 10
+    #[derive(Debug, Clone, Copy)]
+    struct Walk(i64);
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Stride {
+        Step,
+        Jump,
+        Back,
+    }
+
+    impl SearchState for Walk {
+        type Move = Stride;
+        type Key = i64;
+
+        fn successors(&self) -> impl Iterator<Item = (Stride, u64, Self)> {
+            [
+                (Stride::Step, 3, Self(self.0 + 1)),
+                (Stride::Jump, 10, Self(self.0 + 4)),
+                (Stride::Back, 1, Self(self.0 - 1)),
+            ]
+            .into_iter()
+            .filter(|(_, _, w)| (-5..=20).contains(&w.0))
+        }
+
+        fn is_goal(&self) -> bool {
+            self.0 == 10
+        }
+
+        fn heuristic(&self) -> u64 {
+            // Going forwards never costs less than a jump's 2.5 per unit, going back costs 1
+            if self.0 <= 10 {
+                self.0.abs_diff(10) * 5 / 2
+            } else {
+                self.0.abs_diff(10)
+            }
+        }
+
+        fn key(&self) -> Option<i64> {
+            Some(self.0)
+        }
+    }
+
+    fn count(solution: &Solution<Stride>, stride: Stride) -> usize {
+        solution.moves().iter().filter(|&&m| m == stride).count()
+    }
+
+    #[test]
+    fn test_bfs() {
+        // From 3 the fewest moves is three: two jumps to 11 and a step back, costing 21
+        let solution = bfs(Walk(3)).solution.expect("reachable");
+        assert_eq!(3, solution.moves().len());
+        assert_eq!(2, count(&solution, Stride::Jump));
+        assert_eq!(1, count(&solution, Stride::Back));
+        assert_eq!(21, solution.cost());
+    }
+
+    #[test]
+    fn test_cheapest() {
+        // Two jumps and two steps cost 26, beating ten steps for 30 or three jumps and two steps
+        // back for 32
+        for outcome in [dijkstra(Walk(0)), astar(Walk(0)), ida_star(&Walk(0))] {
+            let solution = outcome.solution.expect("reachable");
+            assert_eq!(26, solution.cost());
+            assert_eq!(2, count(&solution, Stride::Jump));
+            assert_eq!(2, count(&solution, Stride::Step));
+        }
+    }
+
+    #[test]
+    fn test_heuristic_saves_work() {
+        let plain = dijkstra(Walk(0)).stats;
+        let guided = astar(Walk(0)).stats;
+        assert!(guided.expanded < plain.expanded, "{guided:?} vs {plain:?}");
+    }
+
+    #[test]
+    fn test_unreachable() {
+        let outcome = dijkstra(Walk(30));
+        assert!(outcome.solution.is_none());
+        assert_eq!(1, outcome.stats.expanded);
+        assert!(ida_star(&Walk(30)).solution.is_none());
+        assert!(bfs(Walk(30)).solution.is_none());
+    }
+}